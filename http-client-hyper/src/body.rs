@@ -0,0 +1,127 @@
+use core::fmt;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use bytes::Bytes;
+use futures_core::Stream;
+use http_body::{Body, Frame, SizeHint};
+use http_client::{BodyContent, BodyStream, BoxError, RequestBody};
+use tokio::io::AsyncRead;
+use tokio_util::io::ReaderStream;
+
+/// Creates a streaming request body that reads the given file.
+///
+/// The file size is used as the body length, so the body is sent with `Content-Length` rather than
+/// chunked transfer-encoding. The file should be positioned at its start.
+pub async fn body_from_file(file: tokio::fs::File) -> std::io::Result<RequestBody> {
+    let length = file.metadata().await?.len();
+    Ok(body_from_reader(file, Some(length)))
+}
+
+/// Creates a streaming request body that reads from the given async reader.
+///
+/// If `length` is `None`, the body is sent using chunked transfer-encoding.
+pub fn body_from_reader<R>(reader: R, length: Option<u64>) -> RequestBody
+where
+    R: AsyncRead + Send + 'static,
+{
+    RequestBody::from_stream(ReaderStream::new(reader), length)
+}
+
+/// The body type sent by [`HyperClient`](crate::HyperClient), adapting a [`RequestBody`] to hyper.
+pub(crate) struct HyperRequestBody {
+    kind: Kind,
+}
+
+enum Kind {
+    Full(Option<Bytes>),
+    Stream {
+        stream: BodyStream,
+        remaining: Option<u64>,
+    },
+}
+
+impl HyperRequestBody {
+    /// Creates an empty body.
+    pub(crate) fn empty() -> Self {
+        Self {
+            kind: Kind::Full(None),
+        }
+    }
+
+    /// Creates a body from the contents of a [`RequestBody`].
+    pub(crate) fn new(content: BodyContent) -> Self {
+        let kind = match content {
+            BodyContent::Bytes(bytes) if bytes.is_empty() => Kind::Full(None),
+            BodyContent::Bytes(bytes) => Kind::Full(Some(bytes)),
+            BodyContent::Stream(stream, length) => Kind::Stream {
+                stream,
+                remaining: length,
+            },
+        };
+        Self { kind }
+    }
+}
+
+impl Body for HyperRequestBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        match &mut self.kind {
+            Kind::Full(bytes) => Poll::Ready(bytes.take().map(|bytes| Ok(Frame::data(bytes)))),
+            Kind::Stream { stream, remaining } => {
+                Pin::new(stream).poll_next(cx).map(|chunk| match chunk {
+                    Some(Ok(chunk)) => {
+                        if let Some(remaining) = remaining {
+                            *remaining = remaining.saturating_sub(chunk.len() as u64);
+                        }
+                        Some(Ok(Frame::data(chunk)))
+                    }
+                    Some(Err(e)) => Some(Err(Box::new(RequestBodyError(e)) as BoxError)),
+                    None => None,
+                })
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match &self.kind {
+            Kind::Full(bytes) => bytes.is_none(),
+            Kind::Stream { remaining, .. } => *remaining == Some(0),
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match &self.kind {
+            Kind::Full(bytes) => SizeHint::with_exact(bytes.as_ref().map_or(0, |b| b.len() as u64)),
+            Kind::Stream {
+                remaining: Some(remaining),
+                ..
+            } => SizeHint::with_exact(*remaining),
+            Kind::Stream {
+                remaining: None, ..
+            } => SizeHint::default(),
+        }
+    }
+}
+
+/// An error of the stream of a request body, wrapped so that it can be recognized in the source
+/// chain of the client errors.
+#[derive(Debug)]
+pub(crate) struct RequestBodyError(BoxError);
+
+impl fmt::Display for RequestBodyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "request body stream error: {}", self.0)
+    }
+}
+
+impl std::error::Error for RequestBodyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.0.as_ref())
+    }
+}
@@ -0,0 +1,220 @@
+use core::time::Duration;
+use std::net::IpAddr;
+
+use hyper_util::client::legacy::{Builder, connect::HttpConnector};
+use hyper_util::rt::{TokioExecutor, TokioTimer};
+
+#[cfg(feature = "cookies")]
+use http_client::CookieJar;
+use http_client::{RedirectPolicy, Timeouts};
+
+use crate::client::{HttpHyperClient, HyperClient};
+use crate::connector::{Connector, Protocols, TcpConnector, TimedResolver, TimeoutConnector};
+
+#[cfg(feature = "rustls")]
+use crate::client::HttpsHyperClient;
+#[cfg(feature = "rustls")]
+use crate::connector::https_connector;
+#[cfg(feature = "rustls")]
+use crate::error::HyperError;
+#[cfg(feature = "rustls")]
+use crate::tls::TlsConfig;
+
+/// Builder for [`HyperClient`], to configure connection pooling, protocols, TCP and TLS options.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use http_client_hyper::HyperClientBuilder;
+///
+/// let client = HyperClientBuilder::new()
+///     .pool_idle_timeout(Some(Duration::from_secs(30)))
+///     .pool_max_idle_per_host(16)
+///     .tcp_nodelay(true)
+///     .build_http();
+/// ```
+#[derive(Debug, Clone)]
+pub struct HyperClientBuilder {
+    client: Builder,
+    connector: HttpConnector<TimedResolver>,
+    protocols: Protocols,
+    timeouts: Timeouts,
+    redirect_policy: RedirectPolicy,
+    #[cfg(feature = "rustls")]
+    tls_config: TlsConfig,
+    #[cfg(feature = "cookies")]
+    cookie_jar: Option<CookieJar>,
+}
+
+impl HyperClientBuilder {
+    /// Creates a new builder with default settings.
+    pub fn new() -> Self {
+        let mut client = Builder::new(TokioExecutor::new());
+        client
+            .timer(TokioTimer::new())
+            .pool_timer(TokioTimer::new());
+        Self {
+            client,
+            connector: HttpConnector::new_with_resolver(TimedResolver::new()),
+            protocols: Protocols::All,
+            timeouts: Timeouts::default(),
+            redirect_policy: RedirectPolicy::default(),
+            #[cfg(feature = "rustls")]
+            tls_config: TlsConfig::default(),
+            #[cfg(feature = "cookies")]
+            cookie_jar: None,
+        }
+    }
+
+    /// Sets how long idle connections are kept in the pool, or `None` to keep them forever.
+    ///
+    /// Defaults to 90 seconds.
+    pub fn pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.client.pool_idle_timeout(timeout);
+        self
+    }
+
+    /// Sets the maximum number of idle connections kept in the pool for each host.
+    ///
+    /// Defaults to no limit.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.client.pool_max_idle_per_host(max);
+        self
+    }
+
+    /// Only uses HTTP/1.1, even if the server supports HTTP/2.
+    pub fn http1_only(mut self) -> Self {
+        self.protocols = Protocols::Http1;
+        self.client.http2_only(false);
+        self
+    }
+
+    /// Only uses HTTP/2.
+    ///
+    /// Over plain HTTP, this assumes the server supports HTTP/2 with prior knowledge (h2c).
+    pub fn http2_only(mut self) -> Self {
+        self.protocols = Protocols::Http2;
+        self.client.http2_only(true);
+        self
+    }
+
+    /// Sets the interval of HTTP/2 keep-alive pings, or `None` to disable them.
+    ///
+    /// Disabled by default.
+    pub fn http2_keep_alive_interval(mut self, interval: Option<Duration>) -> Self {
+        self.client.http2_keep_alive_interval(interval);
+        self
+    }
+
+    /// Sets how long to wait for the acknowledgement of an HTTP/2 keep-alive ping before closing
+    /// the connection.
+    ///
+    /// Defaults to 20 seconds.
+    pub fn http2_keep_alive_timeout(mut self, timeout: Duration) -> Self {
+        self.client.http2_keep_alive_timeout(timeout);
+        self
+    }
+
+    /// Sets whether HTTP/2 keep-alive pings are sent while the connection has no open stream.
+    ///
+    /// Disabled by default.
+    pub fn http2_keep_alive_while_idle(mut self, enabled: bool) -> Self {
+        self.client.http2_keep_alive_while_idle(enabled);
+        self
+    }
+
+    /// Sets the `TCP_NODELAY` option of connections.
+    ///
+    /// Disabled by default.
+    pub fn tcp_nodelay(mut self, nodelay: bool) -> Self {
+        self.connector.set_nodelay(nodelay);
+        self
+    }
+
+    /// Sets the idle time before TCP keep-alive probes are sent, or `None` to disable them.
+    ///
+    /// Disabled by default.
+    pub fn tcp_keepalive(mut self, time: Option<Duration>) -> Self {
+        self.connector.set_keepalive(time);
+        self
+    }
+
+    /// Sets the local address connections are bound to, or `None` to let the OS choose.
+    pub fn local_address(mut self, address: Option<IpAddr>) -> Self {
+        self.connector.set_local_address(address);
+        self
+    }
+
+    /// Sets the default timeouts, used for every request that does not set its own timeouts.
+    pub fn timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Sets the redirect policy.
+    ///
+    /// By default, up to 10 redirects are followed.
+    pub fn redirect_policy(mut self, policy: RedirectPolicy) -> Self {
+        self.redirect_policy = policy;
+        self
+    }
+
+    /// Sets the cookie jar of the client, to store the cookies set by responses and send them
+    /// back with subsequent requests.
+    ///
+    /// Unlike using the jar as a middleware, cookies are also handled on every redirect. Requests
+    /// with their own `Cookie` header are sent without the cookies of the jar.
+    #[cfg(feature = "cookies")]
+    pub fn cookie_jar(mut self, jar: CookieJar) -> Self {
+        self.cookie_jar = Some(jar);
+        self
+    }
+
+    /// Sets the TLS configuration of HTTPS clients.
+    ///
+    /// By default, server certificates are verified against the platform's native root
+    /// certificates.
+    #[cfg(feature = "rustls")]
+    pub fn tls_config(mut self, config: TlsConfig) -> Self {
+        self.tls_config = config;
+        self
+    }
+
+    /// Builds an HTTP-only client.
+    pub fn build_http(self) -> HttpHyperClient {
+        let connector = TcpConnector::new(self.connector.clone());
+        self.build_with_connector(connector)
+    }
+
+    /// Builds an HTTPS-capable client.
+    ///
+    /// Fails if the TLS configuration is invalid, e.g. if no root certificate could be loaded.
+    #[cfg(feature = "rustls")]
+    pub fn build_https(self) -> Result<HttpsHyperClient, HyperError> {
+        let tls_config = self.tls_config.clone().into_client_config()?;
+        let connector = https_connector(self.connector.clone(), self.protocols, tls_config);
+        Ok(self.build_with_connector(connector))
+    }
+
+    /// Builds a client using a custom connector.
+    ///
+    /// The TCP options of this builder (`tcp_nodelay`, `tcp_keepalive` and `local_address`) are
+    /// ignored, since they only apply to the default connector.
+    pub fn build_with_connector<C: Connector>(self, connector: C) -> HyperClient<C> {
+        let inner = self.client.build(TimeoutConnector::new(connector));
+        HyperClient::from_parts(
+            inner,
+            self.timeouts,
+            self.redirect_policy,
+            #[cfg(feature = "cookies")]
+            self.cookie_jar,
+        )
+    }
+}
+
+impl Default for HyperClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,47 +1,107 @@
-use bytes::Bytes;
-use http_body_util::Full;
-use hyper_util::{
-    client::legacy::{Client, connect::HttpConnector},
-    rt::TokioExecutor,
-};
+use hyper_util::client::legacy::Client;
+use hyper_util::client::legacy::connect::HttpInfo;
 
 #[cfg(feature = "rustls")]
 use hyper_rustls::HttpsConnector;
 
-use http_client::{HttpClient, HttpError, HttpMethod, HttpRequest, HttpResponse};
+use hyper::StatusCode;
+use tokio::time::Instant;
+
+#[cfg(feature = "cookies")]
+use http_client::CookieJar;
+use http_client::{
+    HeaderMap, HttpClient, HttpError, HttpMethod, HttpRequest, HttpResponse, HttpVersion,
+    RedirectAction, RedirectAttempt, RedirectPolicy, ResponseMetadata, TimeoutKind, Timeouts,
+    Timings,
+};
 
-use crate::connector::http_connector;
+use crate::body::HyperRequestBody;
+use crate::builder::HyperClientBuilder;
+use crate::connector::{
+    CONNECT_TIMEOUT, ConnectionInfo, Connector, TcpConnector, TimeoutConnector, is_connect_timeout,
+};
+#[cfg(any(
+    feature = "decompress-gzip",
+    feature = "decompress-deflate",
+    feature = "decompress-brotli",
+    feature = "decompress-zstd"
+))]
+use crate::decompress::Encoding;
 use crate::error::HyperError;
 use crate::response::HyperResponseBody;
 
-#[cfg(feature = "rustls")]
-use crate::connector::https_connector;
-
 /// A hyper-based HTTP client.
 ///
 /// The connector type `C` determines what protocols are supported:
-/// - `HttpConnector`: Only HTTP (no TLS).
-/// - `HttpsConnector<HttpConnector>`: Both HTTP and HTTPS (requires `rustls` feature).
+/// - [`TcpConnector`]: Only HTTP (no TLS).
+/// - `HttpsConnector<TcpConnector>`: Both HTTP and HTTPS (requires `rustls` feature).
+///
+/// Clients are configured (connection pool, protocols, timeouts, etc.) with a
+/// [`HyperClientBuilder`].
 pub struct HyperClient<C> {
-    inner: Client<C, Full<Bytes>>,
+    inner: Client<TimeoutConnector<C>, HyperRequestBody>,
+    timeouts: Timeouts,
+    redirect_policy: RedirectPolicy,
+    #[cfg(feature = "cookies")]
+    cookie_jar: Option<CookieJar>,
 }
 
 /// Type alias for an HTTP-only client.
-pub type HttpHyperClient = HyperClient<HttpConnector>;
+pub type HttpHyperClient = HyperClient<TcpConnector>;
 
 /// Type alias for an HTTPS-capable client.
 #[cfg(feature = "rustls")]
-pub type HttpsHyperClient = HyperClient<HttpsConnector<HttpConnector>>;
+pub type HttpsHyperClient = HyperClient<HttpsConnector<TcpConnector>>;
+
+impl<C> HyperClient<C> {
+    pub(crate) fn from_parts(
+        inner: Client<TimeoutConnector<C>, HyperRequestBody>,
+        timeouts: Timeouts,
+        redirect_policy: RedirectPolicy,
+        #[cfg(feature = "cookies")] cookie_jar: Option<CookieJar>,
+    ) -> Self {
+        Self {
+            inner,
+            timeouts,
+            redirect_policy,
+            #[cfg(feature = "cookies")]
+            cookie_jar,
+        }
+    }
+
+    /// Sets the default timeouts of this client.
+    ///
+    /// These are used for every request that does not set its own timeouts. This is equivalent
+    /// to [`HyperClientBuilder::timeouts`].
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Sets the redirect policy of this client.
+    ///
+    /// By default, up to 10 redirects are followed. This is equivalent to
+    /// [`HyperClientBuilder::redirect_policy`].
+    pub fn with_redirect_policy(mut self, policy: RedirectPolicy) -> Self {
+        self.redirect_policy = policy;
+        self
+    }
+}
 
 impl HttpHyperClient {
-    /// Creates a new HTTP-only client.
+    /// Creates a new HTTP-only client with default settings.
     ///
     /// This client can only connect to `http://` URLs.
     /// For HTTPS support, enable the `rustls` feature and use [`HttpsHyperClient::new`].
     pub fn new() -> Self {
-        let connector = http_connector();
-        let client = Client::builder(TokioExecutor::new()).build(connector);
-        Self { inner: client }
+        Self::builder().build_http()
+    }
+
+    /// Creates a builder to configure the client.
+    ///
+    /// This is the same as [`HyperClientBuilder::new`].
+    pub fn builder() -> HyperClientBuilder {
+        HyperClientBuilder::new()
     }
 }
 
@@ -53,13 +113,29 @@ impl Default for HttpHyperClient {
 
 #[cfg(feature = "rustls")]
 impl HttpsHyperClient {
-    /// Creates a new HTTPS-capable client.
+    /// Creates a new HTTPS-capable client with default settings.
     ///
     /// This client can connect to both `http://` and `https://` URLs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the native root certificates cannot be loaded. Use [`HttpsHyperClient::try_new`]
+    /// to handle this case.
     pub fn new() -> Self {
-        let connector = https_connector();
-        let client = Client::builder(TokioExecutor::new()).build(connector);
-        Self { inner: client }
+        Self::try_new().expect("failed to configure TLS")
+    }
+
+    /// Creates a new HTTPS-capable client with default settings, failing if the native root
+    /// certificates cannot be loaded.
+    pub fn try_new() -> Result<Self, HyperError> {
+        Self::builder().build_https()
+    }
+
+    /// Creates a builder to configure the client.
+    ///
+    /// This is the same as [`HyperClientBuilder::new`].
+    pub fn builder() -> HyperClientBuilder {
+        HyperClientBuilder::new()
     }
 }
 
@@ -70,10 +146,7 @@ impl Default for HttpsHyperClient {
     }
 }
 
-impl<C> HttpClient for HyperClient<C>
-where
-    C: hyper_util::client::legacy::connect::Connect + Clone + Send + Sync + 'static,
-{
+impl<C: Connector> HttpClient for HyperClient<C> {
     type Body = HyperResponseBody;
     type Error = HyperError;
 
@@ -81,12 +154,25 @@ where
         &self,
         request: HttpRequest,
     ) -> Result<HttpResponse<Self::Body>, HttpError<Self::Error>> {
+        let timeouts = request.timeouts.or(self.timeouts);
+        let cancellation = request.cancellation;
+
+        // The deadline of the cancellation token bounds the request timeout
+        let deadline = timeouts.request.map(|timeout| Instant::now() + timeout);
+        let deadline = match cancellation.as_ref().and_then(|token| token.deadline()) {
+            Some(cancel_deadline) => {
+                let cancel_deadline = Instant::from_std(cancel_deadline);
+                Some(deadline.map_or(cancel_deadline, |deadline| deadline.min(cancel_deadline)))
+            }
+            None => deadline,
+        };
+
         // Parse the URL
         let mut url = url::Url::parse(&request.url)
             .map_err(|e| HttpError::InvalidUrl(format!("{}: {}", request.url, e)))?;
 
-        // Append query parameters
-        {
+        // Append query parameters, if any (to avoid a trailing `?`)
+        if !request.query.is_empty() {
             let mut query_pairs = url.query_pairs_mut();
             for (key, value) in &request.query {
                 query_pairs.append_pair(key, value);
@@ -94,59 +180,277 @@ where
         }
 
         // Convert method
-        let method = match request.method {
+        let mut method = match &request.method {
             HttpMethod::Get => hyper::Method::GET,
             HttpMethod::Post => hyper::Method::POST,
             HttpMethod::Put => hyper::Method::PUT,
             HttpMethod::Patch => hyper::Method::PATCH,
             HttpMethod::Delete => hyper::Method::DELETE,
+            HttpMethod::Head => hyper::Method::HEAD,
+            HttpMethod::Options => hyper::Method::OPTIONS,
+            HttpMethod::Trace => hyper::Method::TRACE,
+            HttpMethod::Connect => hyper::Method::CONNECT,
+            HttpMethod::Extension(token) => hyper::Method::from_bytes(token.as_bytes())
+                .map_err(|e| HttpError::Client(HyperError::Http(e.into())))?,
         };
 
-        // Build the request
-        let mut builder = hyper::Request::builder().method(method).uri(url.as_str());
+        let mut headers = request.headers;
+        let mut body = request.body;
+        let mut redirects = Vec::new();
 
-        // Add headers
-        for (key, value) in &request.headers {
-            builder = builder.header(key.as_str(), value.as_str());
+        // Advertise the enabled encodings, unless the caller chose its own
+        #[cfg(any(
+            feature = "decompress-gzip",
+            feature = "decompress-deflate",
+            feature = "decompress-brotli",
+            feature = "decompress-zstd"
+        ))]
+        if request.decompress && !headers.contains("accept-encoding") {
+            headers.insert("Accept-Encoding", Encoding::accept_encoding());
         }
 
-        // Set body
-        let body = match request.body {
-            Some(bytes) => Full::new(Bytes::from(bytes)),
-            None => Full::new(Bytes::new()),
-        };
+        // Cookie headers set explicitly on the request take precedence over the jar
+        #[cfg(feature = "cookies")]
+        let cookie_jar = self
+            .cookie_jar
+            .as_ref()
+            .filter(|_| !headers.contains("cookie"));
+
+        loop {
+            // Attach the cookies of the jar matching the current URL, which changes on redirects
+            #[cfg(feature = "cookies")]
+            if let Some(jar) = cookie_jar {
+                match jar.cookie_header(url.as_str()) {
+                    Some(cookies) => headers.insert("Cookie", cookies),
+                    None => {
+                        headers.remove("cookie");
+                    }
+                }
+            }
 
-        let hyper_request = builder
-            .body(body)
-            .map_err(|e| HttpError::Client(HyperError::Http(e)))?;
-
-        // Send request
-        let response = self
-            .inner
-            .request(hyper_request)
-            .await
-            .map_err(|e| HttpError::Client(HyperError::Client(e)))?;
-
-        // Convert response
-        let status = response.status().as_u16();
-
-        let headers: Vec<(String, String)> = response
-            .headers()
-            .iter()
-            .map(|(name, value)| {
-                (
-                    name.as_str().to_string(),
-                    value.to_str().unwrap_or("").to_string(),
-                )
-            })
-            .collect();
-
-        let body = HyperResponseBody::new(response.into_body());
-
-        Ok(HttpResponse {
-            status,
-            headers,
-            body,
-        })
+            // Build the request
+            let mut builder = hyper::Request::builder()
+                .method(method.clone())
+                .uri(url.as_str());
+
+            // Add headers
+            for (name, value) in headers.iter() {
+                builder = builder.header(name, value.as_bytes());
+            }
+
+            // Set body, keeping it around in case it must be sent again after a redirect
+            let hyper_body = match body.clone() {
+                Some(body) => HyperRequestBody::new(
+                    body.into_content()
+                        .map_err(|e| HttpError::Client(HyperError::Body(e.into())))?,
+                ),
+                None => HyperRequestBody::empty(),
+            };
+
+            let hyper_request = builder
+                .body(hyper_body)
+                .map_err(|e| HttpError::Client(HyperError::Http(e)))?;
+
+            // Send request
+            let start = Instant::now();
+            let sending =
+                CONNECT_TIMEOUT.scope(timeouts.connect, self.inner.request(hyper_request));
+            let sending = async {
+                match deadline {
+                    Some(deadline) => tokio::time::timeout_at(deadline, sending)
+                        .await
+                        .map_err(|_| HttpError::Timeout(TimeoutKind::Request)),
+                    None => Ok(sending.await),
+                }
+            };
+            let response = match &cancellation {
+                Some(token) => tokio::select! {
+                    biased;
+                    () = token.cancelled() => Err(HttpError::Cancelled),
+                    result = sending => result,
+                },
+                None => sending.await,
+            }?
+            .map_err(|e| {
+                if is_connect_timeout(&e) {
+                    HttpError::Timeout(TimeoutKind::Connect)
+                } else {
+                    HttpError::Client(HyperError::Client(e))
+                }
+            })?;
+            let metadata = response_metadata(&response, start);
+
+            // Store the cookies set by every response, including redirects
+            #[cfg(feature = "cookies")]
+            if let Some(jar) = &self.cookie_jar {
+                let set_cookies: HeaderMap = response
+                    .headers()
+                    .get_all(hyper::header::SET_COOKIE)
+                    .iter()
+                    .map(|value| ("set-cookie", value.as_bytes()))
+                    .collect();
+                jar.store_response_cookies(url.as_str(), &set_cookies);
+            }
+
+            // Follow redirects
+            let status = response.status();
+            let location = response
+                .headers()
+                .get(hyper::header::LOCATION)
+                .and_then(|location| location.to_str().ok());
+            if let (true, Some(location)) = (is_followable_redirect(status), location) {
+                let next_url = url.join(location).map_err(|e| {
+                    HttpError::Redirect(format!("invalid Location header {location}: {e}"))
+                })?;
+
+                redirects.push(url.to_string());
+                let attempt = RedirectAttempt::new(status.as_u16(), next_url.as_str(), &redirects);
+                let follow = match self.redirect_policy.redirect(&attempt) {
+                    RedirectAction::Follow => true,
+                    RedirectAction::Stop => false,
+                    RedirectAction::Error(reason) => return Err(HttpError::Redirect(reason)),
+                };
+
+                // 303 redirects (and 301/302 for POST, like browsers do) switch to GET without a
+                // body. Other redirects require sending the same request again, which is not
+                // possible with a streaming body.
+                let to_get = (status == StatusCode::SEE_OTHER && method != hyper::Method::HEAD)
+                    || (matches!(status, StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND)
+                        && method == hyper::Method::POST);
+                let replayable = to_get || body.as_ref().is_none_or(|body| body.is_replayable());
+
+                if follow && replayable {
+                    if to_get {
+                        method = hyper::Method::GET;
+                        body = None;
+                        headers.retain(|name| !is_content_header(name));
+                    }
+                    if !is_same_origin(&url, &next_url) {
+                        headers.retain(|name| !is_sensitive_header(name));
+                    }
+                    url = next_url;
+                    continue;
+                }
+
+                redirects.pop();
+            }
+
+            // Convert response
+            let status = http_client::StatusCode::from(response.status().as_u16());
+
+            let headers: HeaderMap = response
+                .headers()
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_bytes()))
+                .collect();
+
+            let body =
+                HyperResponseBody::new(response.into_body(), deadline, timeouts.read, cancellation);
+            // Responses without a body keep the headers describing the encoded representation
+            // (e.g. its Content-Length), since there is nothing to decode.
+            #[cfg(any(
+                feature = "decompress-gzip",
+                feature = "decompress-deflate",
+                feature = "decompress-brotli",
+                feature = "decompress-zstd"
+            ))]
+            let (body, headers) = if request.decompress && has_body(&method, status) {
+                let mut headers = headers;
+                (body.decompressed(&mut headers), headers)
+            } else {
+                (body, headers)
+            };
+
+            let response = HttpResponse {
+                status,
+                headers,
+                body,
+                url: url.to_string(),
+                redirects,
+                metadata,
+            };
+            if request.error_for_status {
+                return response.error_for_status().await;
+            }
+            return Ok(response);
+        }
     }
 }
+
+/// Collects the metadata of a response, whose headers were received just now.
+fn response_metadata<B>(response: &hyper::Response<B>, start: Instant) -> ResponseMetadata {
+    let version = match response.version() {
+        hyper::Version::HTTP_09 => Some(HttpVersion::Http09),
+        hyper::Version::HTTP_10 => Some(HttpVersion::Http10),
+        hyper::Version::HTTP_11 => Some(HttpVersion::Http11),
+        hyper::Version::HTTP_2 => Some(HttpVersion::Http2),
+        hyper::Version::HTTP_3 => Some(HttpVersion::Http3),
+        _ => None,
+    };
+    let (reused, timings) = match response.extensions().get::<ConnectionInfo>() {
+        Some(info) => {
+            let (reused, timings) = info.response();
+            (Some(reused), timings)
+        }
+        None => (None, Timings::default()),
+    };
+
+    ResponseMetadata {
+        version,
+        remote_addr: response
+            .extensions()
+            .get::<HttpInfo>()
+            .map(|info| info.remote_addr()),
+        reused,
+        timings: Timings {
+            ttfb: Some(start.elapsed()),
+            ..timings
+        },
+    }
+}
+
+/// Returns true if the status is a redirect that can be followed automatically.
+fn is_followable_redirect(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::MOVED_PERMANENTLY
+            | StatusCode::FOUND
+            | StatusCode::SEE_OTHER
+            | StatusCode::TEMPORARY_REDIRECT
+            | StatusCode::PERMANENT_REDIRECT
+    )
+}
+
+/// Returns true if the response to a request with the given method may have a body.
+#[cfg(any(
+    feature = "decompress-gzip",
+    feature = "decompress-deflate",
+    feature = "decompress-brotli",
+    feature = "decompress-zstd"
+))]
+fn has_body(method: &hyper::Method, status: http_client::StatusCode) -> bool {
+    *method != hyper::Method::HEAD
+        && !status.is_informational()
+        && status != http_client::StatusCode::NO_CONTENT
+        && status != http_client::StatusCode::NOT_MODIFIED
+}
+
+/// Returns true if both URLs have the same scheme, host and port.
+fn is_same_origin(a: &url::Url, b: &url::Url) -> bool {
+    a.scheme() == b.scheme()
+        && a.host_str() == b.host_str()
+        && a.port_or_known_default() == b.port_or_known_default()
+}
+
+/// Returns true if the header describes the request body, and must be removed along with it.
+fn is_content_header(name: &str) -> bool {
+    matches!(
+        name,
+        "content-type" | "content-length" | "content-encoding" | "transfer-encoding"
+    )
+}
+
+/// Returns true if the header holds credentials that must not be sent to another origin.
+fn is_sensitive_header(name: &str) -> bool {
+    matches!(name, "authorization" | "proxy-authorization" | "cookie")
+}
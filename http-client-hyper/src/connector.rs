@@ -1,23 +1,351 @@
-use hyper_util::client::legacy::connect::HttpConnector;
+use core::fmt;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use core::time::Duration;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use http_client::{BoxError, Timings};
+use hyper::Uri;
+use hyper::rt::{Read, ReadBufCursor, Write};
+use hyper_util::client::legacy::connect::dns::{GaiResolver, Name};
+use hyper_util::client::legacy::connect::{Connected, Connection, HttpConnector};
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpStream;
+use tokio::time::Instant;
+use tower_service::Service;
 
 #[cfg(feature = "rustls")]
 use hyper_rustls::HttpsConnector;
 
-/// Creates an HTTP-only connector.
-pub fn http_connector() -> HttpConnector {
-    HttpConnector::new()
+/// Connectors that can be used by [`HyperClient`](crate::HyperClient).
+///
+/// This is automatically implemented for any `tower` service that connects to a `Uri`, such as
+/// hyper-util's `HttpConnector` or hyper-rustls' `HttpsConnector`.
+pub trait Connector:
+    Service<
+        Uri,
+        Response: Read + Write + Connection + Unpin + Send + 'static,
+        Error: Into<BoxError>,
+        Future: Send + 'static,
+    > + Clone
+    + Send
+    + Sync
+    + 'static
+{
+}
+
+impl<C> Connector for C where
+    C: Service<
+            Uri,
+            Response: Read + Write + Connection + Unpin + Send + 'static,
+            Error: Into<BoxError>,
+            Future: Send + 'static,
+        > + Clone
+        + Send
+        + Sync
+        + 'static
+{
 }
 
-/// Creates an HTTPS connector with rustls.
+/// The HTTP versions a client may use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Protocols {
+    /// HTTP/1.1 and HTTP/2, negotiated with ALPN over TLS (HTTP/1.1 is used over plain HTTP).
+    All,
+    /// HTTP/1.1 only.
+    Http1,
+    /// HTTP/2 only (with prior knowledge over plain HTTP).
+    Http2,
+}
+
+/// Creates an HTTPS connector with rustls, wrapping the given HTTP connector.
 ///
 /// This connector supports both HTTP and HTTPS URLs.
 #[cfg(feature = "rustls")]
-pub fn https_connector() -> HttpsConnector<HttpConnector> {
-    hyper_rustls::HttpsConnectorBuilder::new()
-        .with_native_roots()
-        .expect("failed to load native root certificates")
-        .https_or_http()
-        .enable_http1()
-        .enable_http2()
-        .build()
+pub(crate) fn https_connector(
+    mut http: HttpConnector<TimedResolver>,
+    protocols: Protocols,
+    tls_config: rustls::ClientConfig,
+) -> HttpsConnector<TcpConnector> {
+    // The HTTP connector must let `https://` URLs through for the TLS layer to handle them.
+    http.enforce_http(false);
+    let http = TcpConnector::new(http);
+
+    let builder = hyper_rustls::HttpsConnectorBuilder::new()
+        .with_tls_config(tls_config)
+        .https_or_http();
+    match protocols {
+        Protocols::All => builder.enable_http1().enable_http2().wrap_connector(http),
+        Protocols::Http1 => builder.enable_http1().wrap_connector(http),
+        Protocols::Http2 => builder.enable_http2().wrap_connector(http),
+    }
+}
+
+/// The TCP connector used by [`HttpHyperClient`](crate::HttpHyperClient) and
+/// `HttpsHyperClient`: hyper-util's `HttpConnector`, timing the DNS resolution and TCP connection
+/// phases reported in [`ResponseMetadata::timings`](http_client::ResponseMetadata::timings).
+///
+/// It is configured through [`HyperClientBuilder`](crate::HyperClientBuilder).
+#[derive(Debug, Clone)]
+pub struct TcpConnector {
+    inner: HttpConnector<TimedResolver>,
+}
+
+impl TcpConnector {
+    pub(crate) fn new(inner: HttpConnector<TimedResolver>) -> Self {
+        Self { inner }
+    }
+}
+
+impl Service<Uri> for TcpConnector {
+    type Response = TokioIo<TcpStream>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let connecting = self.inner.call(uri);
+        Box::pin(async move {
+            record(connecting, |phases, elapsed| phases.tcp = Some(elapsed))
+                .await
+                .map_err(Into::into)
+        })
+    }
+}
+
+/// The DNS resolver of [`TcpConnector`], timing resolutions.
+#[derive(Debug, Clone)]
+pub(crate) struct TimedResolver {
+    inner: GaiResolver,
+}
+
+impl TimedResolver {
+    pub(crate) fn new() -> Self {
+        Self {
+            inner: GaiResolver::new(),
+        }
+    }
+}
+
+impl Service<Name> for TimedResolver {
+    type Response = <GaiResolver as Service<Name>>::Response;
+    type Error = <GaiResolver as Service<Name>>::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        let resolving = self.inner.call(name);
+        Box::pin(record(resolving, |phases, elapsed| {
+            phases.dns = Some(elapsed)
+        }))
+    }
+}
+
+/// The phases of a connection recorded by the connectors wrapped by [`TimeoutConnector`], all
+/// measured from the start of the connection.
+#[derive(Debug, Default)]
+struct ConnectPhases {
+    dns: Option<Duration>,
+    tcp: Option<Duration>,
+}
+
+tokio::task_local! {
+    /// The connect timeout of the request being sent, read by [`TimeoutConnector`].
+    pub(crate) static CONNECT_TIMEOUT: Option<Duration>;
+
+    /// The phases of the connection being established by [`TimeoutConnector`].
+    static CONNECT_PHASES: Arc<Mutex<ConnectPhases>>;
+}
+
+/// Awaits `future`, recording how long it took in the phases of the connection being established,
+/// if any.
+async fn record<T>(
+    future: impl Future<Output = T>,
+    phase: impl FnOnce(&mut ConnectPhases, Duration),
+) -> T {
+    let start = Instant::now();
+    let output = future.await;
+    let _ = CONNECT_PHASES.try_with(|phases| {
+        phase(
+            &mut phases
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+            start.elapsed(),
+        )
+    });
+    output
+}
+
+/// Information attached to every connection opened by [`TimeoutConnector`], and added by hyper to
+/// the extensions of the responses received on it.
+#[derive(Debug, Clone)]
+pub(crate) struct ConnectionInfo {
+    timings: Timings,
+    responses: Arc<AtomicUsize>,
+}
+
+impl ConnectionInfo {
+    /// Registers a response received on the connection, returning whether the connection was
+    /// reused, and the timings of its establishment if it was not.
+    pub(crate) fn response(&self) -> (bool, Timings) {
+        if self.responses.fetch_add(1, Ordering::Relaxed) == 0 {
+            (false, self.timings)
+        } else {
+            (true, Timings::default())
+        }
+    }
+}
+
+/// A connection opened by [`TimeoutConnector`], carrying its [`ConnectionInfo`].
+#[derive(Debug)]
+pub(crate) struct InstrumentedConnection<T> {
+    inner: T,
+    info: ConnectionInfo,
+}
+
+impl<T: Connection> Connection for InstrumentedConnection<T> {
+    fn connected(&self) -> Connected {
+        self.inner.connected().extra(self.info.clone())
+    }
+}
+
+impl<T: Read + Unpin> Read for InstrumentedConnection<T> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: ReadBufCursor<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<T: Write + Unpin> Write for InstrumentedConnection<T> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+}
+
+/// A connector wrapper enforcing the connect timeout of the request being sent, and recording the
+/// timings of the connections it opens.
+///
+/// Since hyper only passes the URI to connectors, the timeout is provided through the
+/// [`CONNECT_TIMEOUT`] task-local, which is set by [`HyperClient`](crate::HyperClient) while
+/// sending a request.
+#[derive(Debug, Clone)]
+pub(crate) struct TimeoutConnector<C> {
+    inner: C,
+}
+
+impl<C> TimeoutConnector<C> {
+    pub(crate) fn new(inner: C) -> Self {
+        Self { inner }
+    }
+}
+
+impl<C> Service<Uri> for TimeoutConnector<C>
+where
+    C: Service<Uri>,
+    C::Error: Into<BoxError>,
+    C::Future: Send + 'static,
+{
+    type Response = InstrumentedConnection<C::Response>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let timeout = CONNECT_TIMEOUT.try_with(|timeout| *timeout).ok().flatten();
+        let tls = uri.scheme_str() == Some("https");
+        let start = Instant::now();
+        let phases = Arc::new(Mutex::new(ConnectPhases::default()));
+        let connecting = CONNECT_PHASES.scope(phases.clone(), self.inner.call(uri));
+        Box::pin(async move {
+            let connection = match timeout {
+                Some(timeout) => tokio::time::timeout(timeout, connecting)
+                    .await
+                    .map_err(|_| Box::new(ConnectTimeout) as BoxError)?
+                    .map_err(Into::into),
+                None => connecting.await.map_err(Into::into),
+            }?;
+
+            // Connectors other than `TcpConnector` only give the total time.
+            let total = start.elapsed();
+            let phases = phases
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let tcp = phases.tcp.unwrap_or(total);
+            let timings = Timings {
+                dns: phases.dns,
+                connect: Some(tcp.saturating_sub(phases.dns.unwrap_or_default())),
+                tls: (tls && phases.tcp.is_some()).then(|| total.saturating_sub(tcp)),
+                ttfb: None,
+            };
+            Ok(InstrumentedConnection {
+                inner: connection,
+                info: ConnectionInfo {
+                    timings,
+                    responses: Arc::new(AtomicUsize::new(0)),
+                },
+            })
+        })
+    }
+}
+
+/// Error returned by [`TimeoutConnector`] when the connect timeout elapses.
+#[derive(Debug)]
+pub(crate) struct ConnectTimeout;
+
+impl fmt::Display for ConnectTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("connect timeout elapsed")
+    }
+}
+
+impl std::error::Error for ConnectTimeout {}
+
+/// Returns true if the given error was caused by [`TimeoutConnector`]'s timeout elapsing.
+pub(crate) fn is_connect_timeout(err: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(err);
+    while let Some(err) = source {
+        if err.is::<ConnectTimeout>() {
+            return true;
+        }
+        source = err.source();
+    }
+    false
 }
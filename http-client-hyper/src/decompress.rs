@@ -0,0 +1,168 @@
+use std::io::{self, Cursor};
+
+use bytes::{Bytes, BytesMut};
+use http_body_util::BodyExt;
+use http_client::HeaderMap;
+use hyper::body::Incoming;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio_util::io::StreamReader;
+
+/// The size of the chunks read from a decompressed body.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// A content encoding that responses can be decompressed from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    #[cfg(feature = "decompress-gzip")]
+    Gzip,
+    #[cfg(feature = "decompress-deflate")]
+    Deflate,
+    #[cfg(feature = "decompress-brotli")]
+    Brotli,
+    #[cfg(feature = "decompress-zstd")]
+    Zstd,
+}
+
+impl Encoding {
+    /// The encodings enabled by cargo features, in order of preference.
+    const ENABLED: &[Encoding] = &[
+        #[cfg(feature = "decompress-zstd")]
+        Encoding::Zstd,
+        #[cfg(feature = "decompress-brotli")]
+        Encoding::Brotli,
+        #[cfg(feature = "decompress-gzip")]
+        Encoding::Gzip,
+        #[cfg(feature = "decompress-deflate")]
+        Encoding::Deflate,
+    ];
+
+    /// Returns the value of the `Accept-Encoding` header advertising the enabled encodings.
+    pub(crate) fn accept_encoding() -> String {
+        Self::ENABLED
+            .iter()
+            .map(|encoding| encoding.name())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Parses the value of a `Content-Encoding` header, if it is a single enabled encoding.
+    fn from_header(value: &[u8]) -> Option<Self> {
+        let value = core::str::from_utf8(value).ok()?.trim();
+        Self::ENABLED
+            .iter()
+            .copied()
+            .find(|encoding| encoding.name().eq_ignore_ascii_case(value))
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "decompress-gzip")]
+            Encoding::Gzip => "gzip",
+            #[cfg(feature = "decompress-deflate")]
+            Encoding::Deflate => "deflate",
+            #[cfg(feature = "decompress-brotli")]
+            Encoding::Brotli => "br",
+            #[cfg(feature = "decompress-zstd")]
+            Encoding::Zstd => "zstd",
+        }
+    }
+}
+
+/// A reader decompressing a response body.
+pub(crate) struct Decoder {
+    state: State,
+}
+
+enum State {
+    /// No data has been received yet.
+    Pending(Encoding, Incoming),
+    Decoding(Box<dyn AsyncRead + Send + Sync + Unpin>),
+    Done,
+}
+
+impl Decoder {
+    /// Wraps the body in a decoder if it is compressed with one of the enabled encodings, or
+    /// gives it back otherwise.
+    ///
+    /// The `Content-Encoding` and `Content-Length` headers are removed if the body is decoded, as
+    /// they no longer describe it.
+    pub(crate) fn detect(headers: &mut HeaderMap, body: Incoming) -> Result<Self, Incoming> {
+        let encoding = headers
+            .get("content-encoding")
+            .and_then(|value| Encoding::from_header(value.as_bytes()));
+        match encoding {
+            Some(encoding) => {
+                headers.remove("content-encoding");
+                headers.remove("content-length");
+                Ok(Self {
+                    state: State::Pending(encoding, body),
+                })
+            }
+            None => Err(body),
+        }
+    }
+
+    /// Reads the next decompressed chunk, returning `None` at the end of the body.
+    pub(crate) async fn next_chunk(&mut self) -> io::Result<Option<Bytes>> {
+        // Empty bodies (e.g. HEAD responses) are not valid compressed data, so only start decoding
+        // once some data is received.
+        if let State::Pending(_, body) = &mut self.state {
+            let first = first_chunk(body).await?;
+            self.state = match (core::mem::replace(&mut self.state, State::Done), first) {
+                (State::Pending(encoding, body), Some(first)) => {
+                    State::Decoding(reader(encoding, first, body))
+                }
+                _ => State::Done,
+            };
+        }
+
+        match &mut self.state {
+            State::Decoding(reader) => {
+                let mut buffer = BytesMut::with_capacity(CHUNK_SIZE);
+                let read = reader.read_buf(&mut buffer).await?;
+                Ok((read > 0).then(|| buffer.freeze()))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Reads the first non-empty data chunk of a body, if any.
+async fn first_chunk(body: &mut Incoming) -> io::Result<Option<Bytes>> {
+    while let Some(frame) = body.frame().await {
+        if let Ok(data) = frame.map_err(io::Error::other)?.into_data()
+            && !data.is_empty()
+        {
+            return Ok(Some(data));
+        }
+    }
+    Ok(None)
+}
+
+/// Creates a reader decompressing a body, starting with its already read first chunk.
+fn reader(
+    encoding: Encoding,
+    first: Bytes,
+    body: Incoming,
+) -> Box<dyn AsyncRead + Send + Sync + Unpin> {
+    let rest = StreamReader::new(body.map_err(io::Error::other).into_data_stream());
+    let reader = Cursor::new(first).chain(rest);
+    match encoding {
+        #[cfg(feature = "decompress-gzip")]
+        Encoding::Gzip => {
+            let mut decoder = async_compression::tokio::bufread::GzipDecoder::new(reader);
+            // Concatenated gzip members are valid, and produced by some servers.
+            decoder.multiple_members(true);
+            Box::new(decoder)
+        }
+        // HTTP's `deflate` is actually the zlib format.
+        #[cfg(feature = "decompress-deflate")]
+        Encoding::Deflate => Box::new(async_compression::tokio::bufread::ZlibDecoder::new(reader)),
+        #[cfg(feature = "decompress-brotli")]
+        Encoding::Brotli => Box::new(async_compression::tokio::bufread::BrotliDecoder::new(
+            reader,
+        )),
+        #[cfg(feature = "decompress-zstd")]
+        Encoding::Zstd => Box::new(async_compression::tokio::bufread::ZstdDecoder::new(reader)),
+    }
+}
@@ -1,5 +1,9 @@
 use core::fmt;
 
+use http_client::{BoxError, ClassifyError, TimeoutKind};
+
+use crate::body::RequestBodyError;
+
 /// Errors that can occur when using the Hyper HTTP client.
 #[derive(Debug)]
 pub enum HyperError {
@@ -9,11 +13,28 @@ pub enum HyperError {
     Client(hyper_util::client::legacy::Error),
     /// Error building the HTTP request.
     Http(hyper::http::Error),
-    /// Error reading the response body.
-    Body(String),
+    /// Error reading the request or response body.
+    Body(BoxError),
+    /// A timeout elapsed while reading the response body.
+    ///
+    /// Timeouts elapsing before the response is received are reported as
+    /// [`HttpError::Timeout`](http_client::HttpError::Timeout) instead. Use
+    /// [`ClassifyError::is_timeout`] to detect both without depending on this type.
+    Timeout(TimeoutKind),
+    /// The request was cancelled while reading the response body.
+    Cancelled,
+    /// Invalid TLS configuration.
+    #[cfg(feature = "rustls")]
+    Tls(rustls::Error),
+    /// Error reading PEM-encoded certificates or keys.
+    #[cfg(feature = "rustls")]
+    Pem(rustls::pki_types::pem::Error),
     /// JSON deserialization error.
     #[cfg(feature = "json")]
     Json(serde_json::Error),
+    /// URL-encoded form deserialization error.
+    #[cfg(feature = "form")]
+    Form(serde_urlencoded::de::Error),
 }
 
 impl fmt::Display for HyperError {
@@ -22,9 +43,17 @@ impl fmt::Display for HyperError {
             HyperError::Hyper(e) => write!(f, "hyper error: {e}"),
             HyperError::Client(e) => write!(f, "client error: {e}"),
             HyperError::Http(e) => write!(f, "http error: {e}"),
-            HyperError::Body(msg) => write!(f, "body error: {msg}"),
+            HyperError::Body(e) => write!(f, "body error: {e}"),
+            HyperError::Timeout(kind) => write!(f, "{kind} elapsed"),
+            HyperError::Cancelled => write!(f, "request cancelled"),
+            #[cfg(feature = "rustls")]
+            HyperError::Tls(e) => write!(f, "tls error: {e}"),
+            #[cfg(feature = "rustls")]
+            HyperError::Pem(e) => write!(f, "pem error: {e}"),
             #[cfg(feature = "json")]
             HyperError::Json(e) => write!(f, "json error: {e}"),
+            #[cfg(feature = "form")]
+            HyperError::Form(e) => write!(f, "form error: {e}"),
         }
     }
 }
@@ -35,11 +64,84 @@ impl std::error::Error for HyperError {
             HyperError::Hyper(e) => Some(e),
             HyperError::Client(e) => Some(e),
             HyperError::Http(e) => Some(e),
-            HyperError::Body(_) => None,
+            HyperError::Body(e) => Some(e.as_ref()),
+            HyperError::Timeout(_) => None,
+            HyperError::Cancelled => None,
+            #[cfg(feature = "rustls")]
+            HyperError::Tls(e) => Some(e),
+            #[cfg(feature = "rustls")]
+            HyperError::Pem(e) => Some(e),
             #[cfg(feature = "json")]
             HyperError::Json(e) => Some(e),
+            #[cfg(feature = "form")]
+            HyperError::Form(e) => Some(e),
+        }
+    }
+}
+
+impl ClassifyError for HyperError {
+    fn is_connect(&self) -> bool {
+        match self {
+            HyperError::Client(e) => e.is_connect(),
+            _ => false,
+        }
+    }
+
+    fn is_timeout(&self) -> bool {
+        match self {
+            HyperError::Timeout(_) => true,
+            HyperError::Hyper(e) => e.is_timeout(),
+            HyperError::Client(e) => {
+                find_source::<hyper::Error>(e).is_some_and(|e| e.is_timeout())
+                    || find_source::<std::io::Error>(e)
+                        .is_some_and(|e| e.kind() == std::io::ErrorKind::TimedOut)
+            }
+            _ => false,
+        }
+    }
+
+    fn is_tls(&self) -> bool {
+        match self {
+            #[cfg(feature = "rustls")]
+            HyperError::Tls(_) | HyperError::Pem(_) => true,
+            #[cfg(feature = "rustls")]
+            HyperError::Client(e) => find_source::<rustls::Error>(e).is_some(),
+            _ => false,
+        }
+    }
+
+    fn is_body(&self) -> bool {
+        match self {
+            HyperError::Body(_) => true,
+            HyperError::Client(e) => find_source::<RequestBodyError>(e).is_some(),
+            _ => false,
         }
     }
+
+    fn is_cancelled(&self) -> bool {
+        matches!(self, HyperError::Cancelled)
+    }
+}
+
+/// Finds an error of type `T` in the source chain of an error, including the errors wrapped in
+/// [`std::io::Error`]s, which are not part of their source chain.
+fn find_source<'a, T: std::error::Error + 'static>(
+    err: &'a (dyn std::error::Error + 'static),
+) -> Option<&'a T> {
+    let mut source = Some(err);
+    while let Some(err) = source {
+        if let Some(err) = err.downcast_ref::<T>() {
+            return Some(err);
+        }
+        source = match err
+            .downcast_ref::<std::io::Error>()
+            .and_then(|e| e.get_ref())
+        {
+            Some(inner) => Some(inner as &(dyn std::error::Error + 'static)),
+            None => err.source(),
+        };
+    }
+    None
 }
 
 impl From<hyper::Error> for HyperError {
@@ -60,9 +162,30 @@ impl From<hyper::http::Error> for HyperError {
     }
 }
 
+#[cfg(feature = "rustls")]
+impl From<rustls::Error> for HyperError {
+    fn from(err: rustls::Error) -> Self {
+        HyperError::Tls(err)
+    }
+}
+
+#[cfg(feature = "rustls")]
+impl From<rustls::pki_types::pem::Error> for HyperError {
+    fn from(err: rustls::pki_types::pem::Error) -> Self {
+        HyperError::Pem(err)
+    }
+}
+
 #[cfg(feature = "json")]
 impl From<serde_json::Error> for HyperError {
     fn from(err: serde_json::Error) -> Self {
         HyperError::Json(err)
     }
 }
+
+#[cfg(feature = "form")]
+impl From<serde_urlencoded::de::Error> for HyperError {
+    fn from(err: serde_urlencoded::de::Error) -> Self {
+        HyperError::Form(err)
+    }
+}
@@ -5,7 +5,20 @@
 //! # Features
 //!
 //! - `rustls` - Enables HTTPS support via `hyper-rustls`.
+//! - `webpki-roots` - Allows trusting the Mozilla root certificates bundled by `webpki-roots`
+//!   instead of (or in addition to) the platform's native ones.
 //! - `json` - Enables JSON serialization/deserialization.
+//! - `form` - Enables URL-encoded form serialization/deserialization.
+//! - `query` - Enables serializing query parameters from serde types.
+//! - `cookies` - Enables cookie jars (see [`HyperClientBuilder::cookie_jar`]).
+//! - `decompress-gzip`, `decompress-deflate`, `decompress-brotli`, `decompress-zstd` - Enable
+//!   transparent decompression of response bodies with the corresponding encoding, advertised in
+//!   the `Accept-Encoding` header. Decompression can be disabled per request with
+//!   [`HttpRequestBuilder::decompress`].
+//! - `gzip`, `zstd` - Enable compressing request bodies with [`HttpRequestBuilder::compress`].
+//!
+//! [`HttpRequestBuilder::decompress`]: http_client::HttpRequestBuilder::decompress
+//! [`HttpRequestBuilder::compress`]: http_client::HttpRequestBuilder::compress
 //!
 //! # Example
 //!
@@ -26,17 +39,37 @@
 //! }
 //! ```
 
+mod body;
+mod builder;
 mod client;
 mod connector;
+#[cfg(any(
+    feature = "decompress-gzip",
+    feature = "decompress-deflate",
+    feature = "decompress-brotli",
+    feature = "decompress-zstd"
+))]
+mod decompress;
 mod error;
 mod response;
+mod timer;
+#[cfg(feature = "rustls")]
+mod tls;
 
+pub use body::{body_from_file, body_from_reader};
+pub use builder::HyperClientBuilder;
 pub use client::{HttpHyperClient, HyperClient};
+pub use connector::{Connector, TcpConnector};
 pub use error::HyperError;
 pub use response::HyperResponseBody;
+pub use timer::TokioTimer;
 
 #[cfg(feature = "rustls")]
 pub use client::HttpsHyperClient;
+#[cfg(feature = "rustls")]
+pub use tls::TlsConfig;
 
 // Re-export http-client traits for convenience
-pub use http_client::{HttpClient, HttpError, HttpMethod, HttpRequest, HttpResponse, ResponseBody};
+pub use http_client::{
+    Bytes, HttpClient, HttpError, HttpMethod, HttpRequest, HttpResponse, RequestBody, ResponseBody,
+};
@@ -1,29 +1,160 @@
+use bytes::Bytes;
 use http_body_util::BodyExt;
 use hyper::body::Incoming;
+use tokio::time::{Duration, Instant};
+
+use http_client::{CancellationToken, TimeoutKind};
 
 use crate::error::HyperError;
 
+#[cfg(any(
+    feature = "decompress-gzip",
+    feature = "decompress-deflate",
+    feature = "decompress-brotli",
+    feature = "decompress-zstd"
+))]
+use crate::decompress::Decoder;
+
 /// A wrapper around hyper's `Incoming` body that implements `ResponseBody`.
+///
+/// Compressed bodies are transparently decompressed when the corresponding cargo feature is
+/// enabled, unless the request opted out of it.
 pub struct HyperResponseBody {
-    inner: Incoming,
+    inner: Inner,
+    deadline: Option<Instant>,
+    read_timeout: Option<Duration>,
+    cancellation: Option<CancellationToken>,
+}
+
+enum Inner {
+    Raw(Incoming),
+    #[cfg(any(
+        feature = "decompress-gzip",
+        feature = "decompress-deflate",
+        feature = "decompress-brotli",
+        feature = "decompress-zstd"
+    ))]
+    Decoded(Decoder),
 }
 
 impl HyperResponseBody {
     /// Creates a new `HyperBody` from hyper's `Incoming` body.
-    pub(crate) fn new(incoming: Incoming) -> Self {
-        Self { inner: incoming }
+    ///
+    /// Reads fail once `deadline` is reached, if no data is received within `read_timeout`, or
+    /// once `cancellation` is cancelled.
+    pub(crate) fn new(
+        incoming: Incoming,
+        deadline: Option<Instant>,
+        read_timeout: Option<Duration>,
+        cancellation: Option<CancellationToken>,
+    ) -> Self {
+        Self {
+            inner: Inner::Raw(incoming),
+            deadline,
+            read_timeout,
+            cancellation,
+        }
+    }
+
+    /// Decompresses the body if it is encoded with one of the enabled encodings, updating the
+    /// response headers accordingly.
+    #[cfg(any(
+        feature = "decompress-gzip",
+        feature = "decompress-deflate",
+        feature = "decompress-brotli",
+        feature = "decompress-zstd"
+    ))]
+    pub(crate) fn decompressed(self, headers: &mut http_client::HeaderMap) -> Self {
+        let inner = match self.inner {
+            Inner::Raw(incoming) => match Decoder::detect(headers, incoming) {
+                Ok(decoder) => Inner::Decoded(decoder),
+                Err(incoming) => Inner::Raw(incoming),
+            },
+            inner => inner,
+        };
+        Self { inner, ..self }
+    }
+
+    /// Waits for `read`, enforcing the request deadline, read timeout and cancellation.
+    async fn timed<T>(
+        deadline: Option<Instant>,
+        read_timeout: Option<Duration>,
+        cancellation: Option<&CancellationToken>,
+        read: impl Future<Output = T>,
+    ) -> Result<T, HyperError> {
+        let read = async {
+            match read_timeout {
+                Some(timeout) => tokio::time::timeout(timeout, read)
+                    .await
+                    .map_err(|_| HyperError::Timeout(TimeoutKind::Read)),
+                None => Ok(read.await),
+            }
+        };
+
+        let read = async {
+            match deadline {
+                Some(deadline) => tokio::time::timeout_at(deadline, read)
+                    .await
+                    .map_err(|_| HyperError::Timeout(TimeoutKind::Request))?,
+                None => read.await,
+            }
+        };
+
+        match cancellation {
+            Some(token) => tokio::select! {
+                biased;
+                () = token.cancelled() => Err(HyperError::Cancelled),
+                result = read => result,
+            },
+            None => read.await,
+        }
     }
 }
 
 impl http_client::ResponseBody for HyperResponseBody {
     type Error = HyperError;
 
-    async fn into_bytes(self) -> Result<Vec<u8>, Self::Error> {
-        let collected = self
-            .inner
-            .collect()
-            .await
-            .map_err(|e| HyperError::Body(e.to_string()))?;
-        Ok(collected.to_bytes().to_vec())
+    async fn into_bytes(mut self) -> Result<Vec<u8>, Self::Error> {
+        let mut bytes = Vec::new();
+        while let Some(chunk) = self.next_chunk().await? {
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(bytes)
+    }
+
+    async fn next_chunk(&mut self) -> Result<Option<Bytes>, Self::Error> {
+        match &mut self.inner {
+            Inner::Raw(incoming) => loop {
+                let frame = Self::timed(
+                    self.deadline,
+                    self.read_timeout,
+                    self.cancellation.as_ref(),
+                    incoming.frame(),
+                )
+                .await?;
+                let Some(frame) = frame else {
+                    return Ok(None);
+                };
+                // Trailers are not exposed, so skip any non-data frame.
+                let frame = frame.map_err(|e| HyperError::Body(e.into()))?;
+                if let Ok(data) = frame.into_data() {
+                    return Ok(Some(data));
+                }
+            },
+            #[cfg(any(
+                feature = "decompress-gzip",
+                feature = "decompress-deflate",
+                feature = "decompress-brotli",
+                feature = "decompress-zstd"
+            ))]
+            Inner::Decoded(decoder) => Self::timed(
+                self.deadline,
+                self.read_timeout,
+                self.cancellation.as_ref(),
+                decoder.next_chunk(),
+            )
+            .await?
+            .map_err(|e| HyperError::Body(e.into())),
+        }
     }
 }
@@ -0,0 +1,13 @@
+use core::future::Future;
+use core::time::Duration;
+
+/// A [`Timer`](http_client::Timer) backed by tokio, for use with wrappers like
+/// [`RetryClient`](http_client::RetryClient).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioTimer;
+
+impl http_client::Timer for TokioTimer {
+    fn sleep(&self, duration: Duration) -> impl Future<Output = ()> + Send {
+        tokio::time::sleep(duration)
+    }
+}
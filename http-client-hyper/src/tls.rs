@@ -0,0 +1,275 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use rustls::client::WebPkiServerVerifier;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{
+    CertificateError, ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+
+use crate::error::HyperError;
+
+/// TLS configuration of [`HttpsHyperClient`](crate::HttpsHyperClient)s.
+///
+/// By default, server certificates are verified against the platform's native root certificates.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use http_client_hyper::{HyperClientBuilder, HyperError, TlsConfig};
+///
+/// # fn main() -> Result<(), HyperError> {
+/// let tls = TlsConfig::new()
+///     .native_roots(false)
+///     .add_root_certificates_pem_file("ca.pem")?;
+///
+/// let client = HyperClientBuilder::new().tls_config(tls).build_https()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    native_roots: bool,
+    #[cfg(feature = "webpki-roots")]
+    webpki_roots: bool,
+    roots: Vec<CertificateDer<'static>>,
+    client_auth: Option<(Vec<CertificateDer<'static>>, Arc<PrivateKeyDer<'static>>)>,
+    pins: Vec<[u8; 32]>,
+    custom: Option<ClientConfig>,
+}
+
+impl TlsConfig {
+    /// Creates a new configuration trusting the platform's native root certificates.
+    pub fn new() -> Self {
+        Self {
+            native_roots: true,
+            #[cfg(feature = "webpki-roots")]
+            webpki_roots: false,
+            roots: Vec::new(),
+            client_auth: None,
+            pins: Vec::new(),
+            custom: None,
+        }
+    }
+
+    /// Uses the given rustls configuration as is.
+    ///
+    /// The other options of this type are ignored. ALPN protocols are overwritten by the client,
+    /// according to the protocols it is configured to use.
+    pub fn from_rustls(config: ClientConfig) -> Self {
+        Self {
+            custom: Some(config),
+            ..Self::new()
+        }
+    }
+
+    /// Sets whether the platform's native root certificates are trusted.
+    ///
+    /// Enabled by default. Native roots that are missing or cannot be loaded are skipped, so that
+    /// roots added with other methods are enough; building the client only fails if no root
+    /// certificate is configured at all.
+    pub fn native_roots(mut self, enabled: bool) -> Self {
+        self.native_roots = enabled;
+        self
+    }
+
+    /// Sets whether the Mozilla root certificates bundled by `webpki-roots` are trusted.
+    ///
+    /// Disabled by default.
+    #[cfg(feature = "webpki-roots")]
+    pub fn webpki_roots(mut self, enabled: bool) -> Self {
+        self.webpki_roots = enabled;
+        self
+    }
+
+    /// Trusts the given DER-encoded root certificate.
+    pub fn add_root_certificate(mut self, certificate: CertificateDer<'static>) -> Self {
+        self.roots.push(certificate);
+        self
+    }
+
+    /// Trusts all the root certificates of the given PEM bundle.
+    ///
+    /// Fails if the bundle is invalid or holds no certificate.
+    pub fn add_root_certificates_pem(mut self, pem: &[u8]) -> Result<Self, HyperError> {
+        self.roots.extend(read_certificates(pem)?);
+        Ok(self)
+    }
+
+    /// Trusts all the root certificates of the given PEM file.
+    ///
+    /// Fails if the file cannot be read, is invalid, or holds no certificate.
+    pub fn add_root_certificates_pem_file(
+        mut self,
+        path: impl AsRef<Path>,
+    ) -> Result<Self, HyperError> {
+        let certificates = CertificateDer::pem_file_iter(path)?.collect::<Result<Vec<_>, _>>()?;
+        if certificates.is_empty() {
+            return Err(rustls::pki_types::pem::Error::NoItemsFound.into());
+        }
+        self.roots.extend(certificates);
+        Ok(self)
+    }
+
+    /// Authenticates with the given DER-encoded certificate chain and private key when the server
+    /// requests a client certificate (mutual TLS).
+    pub fn client_auth(
+        mut self,
+        certificate_chain: Vec<CertificateDer<'static>>,
+        key: PrivateKeyDer<'static>,
+    ) -> Self {
+        self.client_auth = Some((certificate_chain, Arc::new(key)));
+        self
+    }
+
+    /// Authenticates with the given PEM-encoded certificate chain and private key when the server
+    /// requests a client certificate (mutual TLS).
+    pub fn client_auth_pem(self, certificate_chain: &[u8], key: &[u8]) -> Result<Self, HyperError> {
+        let certificate_chain = read_certificates(certificate_chain)?;
+        let key = PrivateKeyDer::from_pem_slice(key)?;
+        Ok(self.client_auth(certificate_chain, key))
+    }
+
+    /// Only accepts servers whose certificate has the given SHA-256 fingerprint.
+    ///
+    /// The fingerprint is computed over the DER encoding of the server's end-entity certificate.
+    /// Pinning is done on top of the usual verification, so the certificate must still be issued
+    /// by a trusted root. Can be called several times to accept any of several certificates.
+    pub fn pin_certificate_sha256(mut self, fingerprint: [u8; 32]) -> Self {
+        self.pins.push(fingerprint);
+        self
+    }
+
+    /// Builds the rustls configuration.
+    pub(crate) fn into_client_config(self) -> Result<ClientConfig, HyperError> {
+        if let Some(mut config) = self.custom {
+            config.alpn_protocols.clear();
+            return Ok(config);
+        }
+
+        let mut roots = RootCertStore::empty();
+        // Native roots may be missing (e.g. in minimal containers) while other roots were added,
+        // so failing to load them is only reported if no root is configured at all.
+        let mut native_error = None;
+        if self.native_roots {
+            let native = rustls_native_certs::load_native_certs();
+            native_error = native.errors.first().map(|e| e.to_string());
+            // Some platform certificates may not be parsable by rustls; they are skipped.
+            roots.add_parsable_certificates(native.certs);
+        }
+        #[cfg(feature = "webpki-roots")]
+        if self.webpki_roots {
+            roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        }
+        for certificate in self.roots {
+            roots.add(certificate)?;
+        }
+        if roots.is_empty() {
+            let reason = match native_error {
+                Some(e) => format!(
+                    "no root certificates configured (failed to load native root certificates: {e})"
+                ),
+                None => "no root certificates configured".to_string(),
+            };
+            return Err(HyperError::Tls(rustls::Error::General(reason)));
+        }
+
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let verifier =
+            WebPkiServerVerifier::builder_with_provider(Arc::new(roots), Arc::clone(&provider))
+                .build()
+                .map_err(|e| HyperError::Tls(rustls::Error::General(e.to_string())))?;
+        let builder =
+            ClientConfig::builder_with_provider(provider).with_safe_default_protocol_versions()?;
+        let builder = if self.pins.is_empty() {
+            builder.with_webpki_verifier(verifier)
+        } else {
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(PinningVerifier {
+                    inner: verifier,
+                    pins: self.pins,
+                }))
+        };
+        match self.client_auth {
+            Some((certificate_chain, key)) => {
+                Ok(builder.with_client_auth_cert(certificate_chain, key.clone_key())?)
+            }
+            None => Ok(builder.with_no_client_auth()),
+        }
+    }
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reads all the certificates of a PEM bundle, failing if there are none.
+fn read_certificates(pem: &[u8]) -> Result<Vec<CertificateDer<'static>>, HyperError> {
+    let certificates = CertificateDer::pem_slice_iter(pem).collect::<Result<Vec<_>, _>>()?;
+    if certificates.is_empty() {
+        return Err(rustls::pki_types::pem::Error::NoItemsFound.into());
+    }
+    Ok(certificates)
+}
+
+/// A certificate verifier that also checks the server certificate against pinned fingerprints.
+#[derive(Debug)]
+struct PinningVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    pins: Vec<[u8; 32]>,
+}
+
+impl ServerCertVerifier for PinningVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let verified = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        )?;
+
+        let fingerprint = ring::digest::digest(&ring::digest::SHA256, end_entity);
+        if self.pins.iter().any(|pin| pin == fingerprint.as_ref()) {
+            Ok(verified)
+        } else {
+            Err(rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure,
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
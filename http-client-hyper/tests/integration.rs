@@ -1,5 +1,15 @@
-use http_client::{HttpClient, HttpError, ResponseBody};
-use http_client_hyper::{HttpHyperClient, HyperError};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use http_client::{
+    CancellationToken, ClassifyError, Defaults, Form, HttpClient, HttpError, HttpMethod,
+    HttpRequest, HttpResponse, HttpVersion, MapRequest, Middleware, Next, Part, RedirectAction,
+    RedirectPolicy, ResponseBody, RetryClient, RetryLayer, RetryPolicy, TimeoutKind, Timeouts,
+};
+use http_client_hyper::{
+    HttpHyperClient, HyperError, TokioTimer, body_from_file, body_from_reader,
+};
 use serde::Deserialize;
 use testcontainers::{
     GenericImage,
@@ -25,6 +35,91 @@ async fn start_httpbin() -> (testcontainers::ContainerAsync<GenericImage>, Strin
     (container, base_url)
 }
 
+/// Client wrapper counting the requests sent through it.
+struct CountingClient<C> {
+    inner: C,
+    count: AtomicUsize,
+}
+
+impl<C> CountingClient<C> {
+    fn new(inner: C) -> Self {
+        Self {
+            inner,
+            count: AtomicUsize::new(0),
+        }
+    }
+
+    fn count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+}
+
+impl<C: HttpClient + Sync> HttpClient for CountingClient<C> {
+    type Body = C::Body;
+    type Error = C::Error;
+
+    async fn send(
+        &self,
+        request: HttpRequest,
+    ) -> Result<HttpResponse<Self::Body>, HttpError<Self::Error>> {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        self.inner.send(request).await
+    }
+}
+
+/// Middleware appending a value to the `X-Trace` header of every request.
+struct TraceMiddleware(&'static str);
+
+impl<C: HttpClient + Sync> Middleware<C> for TraceMiddleware {
+    async fn handle(
+        &self,
+        mut request: HttpRequest,
+        next: Next<'_, C>,
+    ) -> Result<HttpResponse<C::Body>, HttpError<C::Error>> {
+        let trace = match request.headers.get("X-Trace") {
+            Some(value) => format!("{},{}", value.to_str().unwrap(), self.0),
+            None => self.0.to_string(),
+        };
+        request.headers.insert("X-Trace", trace);
+        next.run(request).await
+    }
+}
+
+/// Middleware recording the status of every response.
+#[derive(Default)]
+struct StatusRecorder(Mutex<Vec<u16>>);
+
+impl<C: HttpClient + Sync> Middleware<C> for StatusRecorder {
+    async fn handle(
+        &self,
+        request: HttpRequest,
+        next: Next<'_, C>,
+    ) -> Result<HttpResponse<C::Body>, HttpError<C::Error>> {
+        let response = next.run(request).await?;
+        self.0.lock().unwrap().push(response.status.as_u16());
+        Ok(response)
+    }
+}
+
+/// Middleware rejecting every request without sending it.
+struct RejectMiddleware;
+
+impl<C: HttpClient + Sync> Middleware<C> for RejectMiddleware {
+    async fn handle(
+        &self,
+        request: HttpRequest,
+        _next: Next<'_, C>,
+    ) -> Result<HttpResponse<C::Body>, HttpError<C::Error>> {
+        Err(HttpError::InvalidUrl(request.url))
+    }
+}
+
+fn fast_retry_policy() -> RetryPolicy {
+    RetryPolicy::new()
+        .max_attempts(3)
+        .backoff(Duration::from_millis(10), Duration::from_millis(50))
+}
+
 #[derive(Debug, Deserialize)]
 struct HttpbinResponse {
     url: String,
@@ -36,6 +131,10 @@ struct HttpbinResponse {
     data: String,
     #[serde(default)]
     json: Option<serde_json::Value>,
+    #[serde(default)]
+    form: std::collections::HashMap<String, String>,
+    #[serde(default)]
+    files: std::collections::HashMap<String, String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -124,6 +223,105 @@ async fn test_delete_request() {
     assert_eq!(response.status, 200);
 }
 
+#[tokio::test]
+async fn test_client_builder() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::builder()
+        .pool_idle_timeout(Some(Duration::from_secs(5)))
+        .pool_max_idle_per_host(2)
+        .http1_only()
+        .tcp_nodelay(true)
+        .tcp_keepalive(Some(Duration::from_secs(30)))
+        .build_http();
+
+    for _ in 0..3 {
+        let request = client.get(format!("{}/get", base_url)).build();
+        let response = client.send(request).await.unwrap();
+        assert!(response.is_success());
+        response.body.into_bytes().await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_client_builder_local_address() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::builder()
+        .local_address(Some("0.0.0.0".parse().unwrap()))
+        .build_http();
+
+    let request = client.get(format!("{}/get", base_url)).build();
+    let response = client.send(request).await.unwrap();
+
+    assert!(response.is_success());
+}
+
+#[tokio::test]
+async fn test_head_request() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::new();
+
+    let request = client.head(format!("{}/get", base_url)).build();
+    let response = client.send(request).await.unwrap();
+
+    assert!(response.is_success());
+
+    let bytes = response.body.into_bytes().await.unwrap();
+    assert!(bytes.is_empty());
+}
+
+#[tokio::test]
+async fn test_options_request() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::new();
+
+    let request = client.options(format!("{}/get", base_url)).build();
+    let response = client.send(request).await.unwrap();
+
+    assert!(response.is_success());
+
+    let allow = response.headers.get("Allow").unwrap().to_str().unwrap();
+    assert!(allow.contains("GET"));
+}
+
+#[tokio::test]
+async fn test_extension_method() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::new();
+
+    let request = client
+        .request(
+            HttpMethod::from("PROPFIND"),
+            format!("{}/anything", base_url),
+        )
+        .build();
+    let response = client.send(request).await.unwrap();
+
+    assert!(response.is_success());
+
+    let body_bytes = response.body.into_bytes().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(body["method"], "PROPFIND");
+}
+
+#[tokio::test]
+async fn test_invalid_extension_method() {
+    let client = HttpHyperClient::new();
+
+    let request = client
+        .request(
+            HttpMethod::Extension("NOT A TOKEN".to_string()),
+            "http://127.0.0.1:59999/get",
+        )
+        .build();
+    let result = client.send(request).await;
+
+    match result {
+        Err(HttpError::Client(HyperError::Http(_))) => {}
+        Err(other) => panic!("Expected Http error, got: {:?}", other),
+        Ok(_) => panic!("Expected error, got Ok"),
+    }
+}
+
 #[tokio::test]
 async fn test_query_parameters() {
     let (_container, base_url) = start_httpbin().await;
@@ -206,12 +404,11 @@ async fn test_response_headers() {
 
     assert!(response.is_success());
 
-    let header = response
-        .headers
-        .iter()
-        .find(|(k, _)| k.eq_ignore_ascii_case("x-test-header"));
-    assert!(header.is_some());
-    assert_eq!(header.unwrap().1, "test-value");
+    assert_eq!(response.headers.get("X-Test-Header").unwrap(), "test-value");
+    assert_eq!(
+        response.headers.get("x-test-header"),
+        response.headers.get("X-TEST-HEADER")
+    );
 }
 
 #[tokio::test]
@@ -229,6 +426,153 @@ async fn test_post_raw_body() {
     assert!(response.is_success());
 }
 
+#[tokio::test]
+async fn test_post_multipart_form() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::new();
+
+    let form = Form::new().text("title", "Hello").part(
+        "file",
+        Part::bytes("file content")
+            .file_name("hello.txt")
+            .content_type("text/plain"),
+    );
+    let request = client
+        .post(format!("{}/post", base_url))
+        .multipart(form)
+        .build();
+    let response = client.send(request).await.unwrap();
+
+    assert!(response.is_success());
+
+    let body_bytes = response.body.into_bytes().await.unwrap();
+    let body: HttpbinResponse = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(body.form.get("title"), Some(&"Hello".to_string()));
+    assert_eq!(body.files.get("file"), Some(&"file content".to_string()));
+    assert!(
+        body.headers
+            .get("Content-Type")
+            .is_some_and(|content_type| content_type.starts_with("multipart/form-data; boundary="))
+    );
+}
+
+#[tokio::test]
+async fn test_post_multipart_streaming_part() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::new();
+
+    let content: &'static [u8] = b"streamed file content";
+    let form = Form::new().text("title", "Hello").part(
+        "file",
+        Part::new(body_from_reader(content, Some(content.len() as u64))).file_name("file.txt"),
+    );
+    let request = client
+        .post(format!("{}/post", base_url))
+        .multipart(form)
+        .build();
+    let response = client.send(request).await.unwrap();
+
+    assert!(response.is_success());
+
+    let body_bytes = response.body.into_bytes().await.unwrap();
+    let body: HttpbinResponse = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(body.form.get("title"), Some(&"Hello".to_string()));
+    assert_eq!(
+        body.files.get("file"),
+        Some(&"streamed file content".to_string())
+    );
+    assert!(body.headers.contains_key("Content-Length"));
+}
+
+#[tokio::test]
+async fn test_post_streaming_body_with_length() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::new();
+
+    let content: &'static [u8] = b"streamed body content";
+    let request = client
+        .post(format!("{}/post", base_url))
+        .body(body_from_reader(content, Some(content.len() as u64)))
+        .build();
+    let response = client.send(request).await.unwrap();
+
+    assert!(response.is_success());
+
+    let body_bytes = response.body.into_bytes().await.unwrap();
+    let body: HttpbinResponse = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(body.data, "streamed body content");
+    assert_eq!(
+        body.headers.get("Content-Length"),
+        Some(&content.len().to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_post_streaming_body_chunked() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::new();
+
+    let content: &'static [u8] = b"chunked body content";
+    let request = client
+        .post(format!("{}/anything", base_url))
+        .body(body_from_reader(content, None))
+        .build();
+    let response = client.send(request).await.unwrap();
+
+    assert!(response.is_success());
+
+    let body_bytes = response.body.into_bytes().await.unwrap();
+    let body: HttpbinResponse = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(
+        body.headers.get("Transfer-Encoding"),
+        Some(&"chunked".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_post_file_body() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::new();
+
+    let path = std::env::temp_dir().join("http-client-hyper-test-post-file-body.txt");
+    tokio::fs::write(&path, "file body content").await.unwrap();
+    let file = tokio::fs::File::open(&path).await.unwrap();
+
+    let request = client
+        .post(format!("{}/post", base_url))
+        .body(body_from_file(file).await.unwrap())
+        .build();
+    let response = client.send(request).await.unwrap();
+    tokio::fs::remove_file(&path).await.unwrap();
+
+    assert!(response.is_success());
+
+    let body_bytes = response.body.into_bytes().await.unwrap();
+    let body: HttpbinResponse = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(body.data, "file body content");
+}
+
+#[tokio::test]
+async fn test_streaming_body_consumed_once() {
+    let client = HttpHyperClient::new();
+
+    let content: &'static [u8] = b"one-shot";
+    let request = client
+        .post("http://127.0.0.1:59999/post")
+        .body(body_from_reader(content, None))
+        .build();
+    assert!(!request.body.as_ref().unwrap().is_replayable());
+
+    // The first attempt consumes the stream, even though the connection fails.
+    let _ = client.send(request.clone()).await;
+
+    match client.send(request).await {
+        Err(e @ HttpError::Client(HyperError::Body(_))) => assert!(e.is_body()),
+        Err(other) => panic!("Expected Body error, got: {:?}", other),
+        Ok(_) => panic!("Expected error, got Ok"),
+    }
+}
+
 #[tokio::test]
 async fn test_response_status_codes() {
     let (_container, base_url) = start_httpbin().await;
@@ -280,69 +624,844 @@ async fn test_response_body_bytes() {
 }
 
 #[tokio::test]
-async fn test_invalid_url() {
+async fn test_response_body_chunks() {
+    let (_container, base_url) = start_httpbin().await;
     let client = HttpHyperClient::new();
 
-    let request = client.get("not-a-valid-url").build();
-    let result = client.send(request).await;
+    let request = client
+        .get(format!("{}/stream-bytes/4096", base_url))
+        .query("chunk_size", "512")
+        .build();
+    let mut response = client.send(request).await.unwrap();
 
-    match result {
-        Err(HttpError::InvalidUrl(_)) => {}
-        Err(other) => panic!("Expected InvalidUrl error, got: {:?}", other),
-        Ok(_) => panic!("Expected error, got Ok"),
+    assert!(response.is_success());
+
+    let mut chunks = 0;
+    let mut total = 0;
+    while let Some(chunk) = response.body.next_chunk().await.unwrap() {
+        chunks += 1;
+        total += chunk.len();
     }
+    assert!(chunks > 1);
+    assert_eq!(total, 4096);
+
+    // Once exhausted, the body keeps returning `None`.
+    assert!(response.body.next_chunk().await.unwrap().is_none());
 }
 
 #[tokio::test]
-async fn test_connection_refused() {
+async fn test_request_timeout() {
+    let (_container, base_url) = start_httpbin().await;
     let client = HttpHyperClient::new();
 
-    // Try to connect to a port that's not listening
-    let request = client.get("http://127.0.0.1:59999/get").build();
+    let request = client
+        .get(format!("{}/delay/3", base_url))
+        .timeout(Duration::from_millis(500))
+        .build();
     let result = client.send(request).await;
 
     match result {
-        Err(HttpError::Client(HyperError::Client(_))) => {}
-        Err(other) => panic!("Expected Client error, got: {:?}", other),
+        Err(HttpError::Timeout(TimeoutKind::Request)) => {}
+        Err(other) => panic!("Expected request Timeout error, got: {:?}", other),
         Ok(_) => panic!("Expected error, got Ok"),
     }
 }
 
-#[cfg(feature = "json")]
-mod json_tests {
-    use super::*;
+#[tokio::test]
+async fn test_client_default_timeout() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::builder()
+        .timeouts(Timeouts {
+            request: Some(Duration::from_millis(500)),
+            ..Timeouts::default()
+        })
+        .build_http();
+
+    let request = client.get(format!("{}/delay/3", base_url)).build();
+    let result = client.send(request).await;
+    assert!(matches!(
+        result,
+        Err(HttpError::Timeout(TimeoutKind::Request))
+    ));
 
-    #[derive(Debug, serde::Serialize, Deserialize, PartialEq)]
-    struct TestPayload {
-        name: String,
-        value: i32,
-    }
+    // Request timeouts take precedence over the client defaults.
+    let request = client
+        .get(format!("{}/delay/1", base_url))
+        .timeout(Duration::from_secs(5))
+        .build();
+    let response = client.send(request).await.unwrap();
+    assert!(response.is_success());
+}
 
-    #[tokio::test]
-    async fn test_json_response() {
-        let (_container, base_url) = start_httpbin().await;
-        let client = HttpHyperClient::new();
+#[tokio::test]
+async fn test_read_timeout() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::new();
 
-        let request = client.get(format!("{}/get", base_url)).build();
-        let response = client.send(request).await.unwrap();
+    let request = client
+        .get(format!("{}/drip", base_url))
+        .query("duration", "3")
+        .query("numbytes", "3")
+        .read_timeout(Duration::from_millis(300))
+        .build();
+    let response = client.send(request).await.unwrap();
 
-        assert!(response.is_success());
+    match response.body.into_bytes().await {
+        Err(e @ HyperError::Timeout(TimeoutKind::Read)) => assert!(e.is_timeout()),
+        Err(other) => panic!("Expected read Timeout error, got: {:?}", other),
+        Ok(_) => panic!("Expected error, got Ok"),
+    }
+}
 
-        let body: HttpbinResponse = response.body.json().await.unwrap();
-        assert!(body.url.contains("/get"));
+/// Connector whose connections never get established.
+#[derive(Clone)]
+struct StalledConnector;
+
+impl tower_service::Service<hyper::Uri> for StalledConnector {
+    type Response = hyper_util::rt::TokioIo<tokio::net::TcpStream>;
+    type Error = std::io::Error;
+    type Future = std::future::Pending<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
     }
 
-    #[tokio::test]
-    async fn test_post_json_body() {
-        let (_container, base_url) = start_httpbin().await;
-        let client = HttpHyperClient::new();
+    fn call(&mut self, _uri: hyper::Uri) -> Self::Future {
+        std::future::pending()
+    }
+}
 
-        let payload = TestPayload {
-            name: "test".to_string(),
-            value: 42,
-        };
+#[tokio::test]
+async fn test_connect_timeout() {
+    let client = HttpHyperClient::builder().build_with_connector(StalledConnector);
 
-        let request = client
+    let request = client
+        .get("http://example.com/")
+        .connect_timeout(Duration::from_millis(200))
+        .build();
+    let result = tokio::time::timeout(Duration::from_secs(5), client.send(request))
+        .await
+        .expect("the connect timeout should have elapsed");
+
+    match result {
+        Err(e @ HttpError::Timeout(TimeoutKind::Connect)) => {
+            assert!(e.is_timeout());
+            assert!(e.is_connect());
+        }
+        Err(other) => panic!("Expected connect Timeout error, got: {:?}", other),
+        Ok(_) => panic!("Expected error, got Ok"),
+    }
+
+    // Connect timeouts can also be set as a default of the client.
+    let client = HttpHyperClient::builder()
+        .build_with_connector(StalledConnector)
+        .with_timeouts(Timeouts {
+            connect: Some(Duration::from_millis(200)),
+            ..Timeouts::default()
+        });
+    let request = client.get("http://example.com/").build();
+    let result = tokio::time::timeout(Duration::from_secs(5), client.send(request))
+        .await
+        .expect("the connect timeout should have elapsed");
+    assert!(matches!(
+        result,
+        Err(HttpError::Timeout(TimeoutKind::Connect))
+    ));
+}
+
+#[tokio::test]
+async fn test_retry_on_status() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = RetryClient::new(CountingClient::new(HttpHyperClient::new()), TokioTimer)
+        .with_policy(fast_retry_policy());
+
+    let request = client.get(format!("{}/status/503", base_url)).build();
+    let response = client.send(request).await.unwrap();
+
+    // The last response is returned as is once all attempts are exhausted.
+    assert_eq!(response.status, 503);
+    assert_eq!(client.inner().count(), 3);
+}
+
+#[tokio::test]
+async fn test_retry_not_on_success() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = RetryClient::new(CountingClient::new(HttpHyperClient::new()), TokioTimer)
+        .with_policy(fast_retry_policy());
+
+    let request = client.get(format!("{}/status/404", base_url)).build();
+    let response = client.send(request).await.unwrap();
+
+    assert_eq!(response.status, 404);
+    assert_eq!(client.inner().count(), 1);
+}
+
+#[tokio::test]
+async fn test_retry_non_idempotent() {
+    let (_container, base_url) = start_httpbin().await;
+
+    let client = RetryClient::new(CountingClient::new(HttpHyperClient::new()), TokioTimer)
+        .with_policy(fast_retry_policy());
+    let request = client.post(format!("{}/status/503", base_url)).build();
+    client.send(request).await.unwrap();
+    assert_eq!(client.inner().count(), 1);
+
+    let client = RetryClient::new(CountingClient::new(HttpHyperClient::new()), TokioTimer)
+        .with_policy(fast_retry_policy().retry_non_idempotent(true));
+    let request = client.post(format!("{}/status/503", base_url)).build();
+    client.send(request).await.unwrap();
+    assert_eq!(client.inner().count(), 3);
+}
+
+#[tokio::test]
+async fn test_retry_on_connection_error() {
+    let client = RetryClient::new(CountingClient::new(HttpHyperClient::new()), TokioTimer)
+        .with_policy(fast_retry_policy());
+
+    let request = client.get("http://127.0.0.1:59999/get").build();
+    let result = client.send(request).await;
+
+    assert!(matches!(
+        result,
+        Err(HttpError::Client(HyperError::Client(_)))
+    ));
+    assert_eq!(client.inner().count(), 3);
+}
+
+#[tokio::test]
+async fn test_middleware_order() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::new()
+        .with_middleware(TraceMiddleware("inner"))
+        .with_middleware(TraceMiddleware("outer"));
+
+    let request = client.get(format!("{}/headers", base_url)).build();
+    let response = client.send(request).await.unwrap();
+
+    assert!(response.is_success());
+
+    let body_bytes = response.body.into_bytes().await.unwrap();
+    let body: HttpbinHeadersResponse = serde_json::from_slice(&body_bytes).unwrap();
+
+    // The last middleware added is the first to handle the request.
+    assert_eq!(
+        body.headers.get("X-Trace"),
+        Some(&"outer,inner".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_middleware_response() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::new()
+        .with_middleware(StatusRecorder::default())
+        .with_layer(RetryLayer::new(TokioTimer, fast_retry_policy()));
+
+    let request = client.get(format!("{}/status/503", base_url)).build();
+    client.send(request).await.unwrap();
+    let request = client.get(format!("{}/status/201", base_url)).build();
+    client.send(request).await.unwrap();
+
+    // Retries happen outside of the recorder, so it sees every attempt.
+    let statuses = client.inner().middleware().0.lock().unwrap().clone();
+    assert_eq!(statuses, vec![503, 503, 503, 201]);
+}
+
+#[tokio::test]
+async fn test_map_request_middleware() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::new().with_middleware(MapRequest::new(|request| HttpRequest {
+        query: vec![("mapped".to_string(), "yes".to_string())],
+        ..request
+    }));
+
+    let request = client.get(format!("{}/get", base_url)).build();
+    let response = client.send(request).await.unwrap();
+
+    let body_bytes = response.body.into_bytes().await.unwrap();
+    let body: HttpbinResponse = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(body.args.get("mapped"), Some(&"yes".to_string()));
+}
+
+#[tokio::test]
+async fn test_defaults_middleware() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::new().with_middleware(
+        Defaults::new()
+            .base_url(&base_url)
+            .header("User-Agent", "http-client-tests")
+            .query("source", "defaults"),
+    );
+
+    let request = client.get("/get").query("page", "1").build();
+    let response = client.send(request).await.unwrap();
+
+    assert_eq!(
+        response.url,
+        format!("{}/get?page=1&source=defaults", base_url)
+    );
+    let body_bytes = response.body.into_bytes().await.unwrap();
+    let body: HttpbinResponse = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(
+        body.headers.get("User-Agent"),
+        Some(&"http-client-tests".to_string())
+    );
+    assert_eq!(body.args.get("source"), Some(&"defaults".to_string()));
+}
+
+#[tokio::test]
+async fn test_basic_and_bearer_auth() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::new();
+
+    let request = client
+        .get(format!("{}/basic-auth/user/passwd", base_url))
+        .basic_auth("user", "passwd")
+        .build();
+    let response = client.send(request).await.unwrap();
+    assert_eq!(response.status, 200);
+
+    let request = client
+        .get(format!("{}/basic-auth/user/passwd", base_url))
+        .basic_auth("user", "wrong")
+        .build();
+    let response = client.send(request).await.unwrap();
+    assert_eq!(response.status, 401);
+
+    let request = client
+        .get(format!("{}/bearer", base_url))
+        .bearer_auth("secret-token")
+        .build();
+    let response = client.send(request).await.unwrap();
+    assert_eq!(response.status, 200);
+    let body: serde_json::Value =
+        serde_json::from_slice(&response.body.into_bytes().await.unwrap()).unwrap();
+    assert_eq!(body["token"], "secret-token");
+}
+
+#[tokio::test]
+async fn test_error_for_status() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::new();
+
+    let request = client
+        .get(format!("{}/status/418", base_url))
+        .error_for_status(true)
+        .build();
+    match client.send(request).await {
+        Err(HttpError::Status(e)) => {
+            assert_eq!(e.status(), 418);
+            // httpbin answers with a teapot drawing
+            assert!(String::from_utf8_lossy(e.body()).contains("teapot"));
+        }
+        Err(other) => panic!("Expected Status error, got: {:?}", other),
+        Ok(_) => panic!("Expected error, got Ok"),
+    }
+
+    let request = client
+        .get(format!("{}/status/200", base_url))
+        .error_for_status(true)
+        .build();
+    assert_eq!(client.send(request).await.unwrap().status, 200);
+}
+
+#[tokio::test]
+async fn test_middleware_short_circuit() {
+    let client = CountingClient::new(HttpHyperClient::new()).with_middleware(RejectMiddleware);
+
+    let request = client.get("http://127.0.0.1:59999/get").build();
+    let result = client.send(request).await;
+
+    assert!(matches!(result, Err(HttpError::InvalidUrl(_))));
+    assert_eq!(client.inner().count(), 0);
+}
+
+#[tokio::test]
+async fn test_follow_redirects() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::new();
+
+    let request = client.get(format!("{}/redirect/3", base_url)).build();
+    let response = client.send(request).await.unwrap();
+
+    assert!(response.is_success());
+    assert_eq!(response.url, format!("{}/get", base_url));
+    assert_eq!(response.redirects.len(), 3);
+    assert_eq!(response.redirects[0], format!("{}/redirect/3", base_url));
+}
+
+#[tokio::test]
+async fn test_redirect_policy_none() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::new().with_redirect_policy(RedirectPolicy::none());
+
+    let request = client.get(format!("{}/redirect/1", base_url)).build();
+    let response = client.send(request).await.unwrap();
+
+    assert_eq!(response.status, 302);
+    assert_eq!(response.url, format!("{}/redirect/1", base_url));
+    assert!(response.redirects.is_empty());
+}
+
+#[tokio::test]
+async fn test_redirect_policy_limited() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::builder()
+        .redirect_policy(RedirectPolicy::limited(2))
+        .build_http();
+
+    let request = client.get(format!("{}/redirect/3", base_url)).build();
+    let result = client.send(request).await;
+
+    match result {
+        Err(e @ HttpError::Redirect(_)) => assert!(e.is_redirect()),
+        Err(other) => panic!("Expected Redirect error, got: {:?}", other),
+        Ok(_) => panic!("Expected error, got Ok"),
+    }
+}
+
+#[tokio::test]
+async fn test_redirect_policy_limited_boundary() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::builder()
+        .redirect_policy(RedirectPolicy::limited(2))
+        .build_http();
+
+    // Exactly as many redirects as allowed are followed
+    let request = client.get(format!("{}/redirect/2", base_url)).build();
+    let response = client.send(request).await.unwrap();
+
+    assert_eq!(response.status, 200);
+    assert_eq!(response.redirects.len(), 2);
+}
+
+#[tokio::test]
+async fn test_redirect_policy_custom() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::builder()
+        .redirect_policy(RedirectPolicy::custom(|attempt| {
+            if attempt.url().ends_with("/get") {
+                RedirectAction::Stop
+            } else {
+                RedirectAction::Follow
+            }
+        }))
+        .build_http();
+
+    let request = client.get(format!("{}/redirect/3", base_url)).build();
+    let response = client.send(request).await.unwrap();
+
+    assert_eq!(response.status, 302);
+    assert_eq!(response.redirects.len(), 2);
+}
+
+#[tokio::test]
+async fn test_redirect_rewrites_method() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::new();
+
+    // 303 switches to GET and drops the body.
+    let request = client
+        .post(format!("{}/redirect-to", base_url))
+        .query("url", "/anything")
+        .query("status_code", "303")
+        .body("redirected body")
+        .build();
+    let response = client.send(request).await.unwrap();
+
+    let body: serde_json::Value =
+        serde_json::from_slice(&response.body.into_bytes().await.unwrap()).unwrap();
+    assert_eq!(body["method"], "GET");
+    assert_eq!(body["data"], "");
+
+    // 307 keeps the method and body.
+    let request = client
+        .post(format!("{}/redirect-to", base_url))
+        .query("url", "/anything")
+        .query("status_code", "307")
+        .body("redirected body")
+        .build();
+    let response = client.send(request).await.unwrap();
+
+    let body: serde_json::Value =
+        serde_json::from_slice(&response.body.into_bytes().await.unwrap()).unwrap();
+    assert_eq!(body["method"], "POST");
+    assert_eq!(body["data"], "redirected body");
+}
+
+#[tokio::test]
+async fn test_redirect_strips_sensitive_headers_cross_origin() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::new();
+
+    // Same server, but reached through another host name, so another origin.
+    let other_origin = if base_url.contains("localhost") {
+        base_url.replace("localhost", "127.0.0.1")
+    } else {
+        base_url.replace("127.0.0.1", "localhost")
+    };
+
+    let request = client
+        .get(format!("{}/redirect-to", base_url))
+        .query("url", format!("{}/headers", base_url))
+        .header("Authorization", "Bearer secret")
+        .build();
+    let response = client.send(request).await.unwrap();
+    let body: HttpbinHeadersResponse =
+        serde_json::from_slice(&response.body.into_bytes().await.unwrap()).unwrap();
+    assert_eq!(
+        body.headers.get("Authorization"),
+        Some(&"Bearer secret".to_string())
+    );
+
+    let request = client
+        .get(format!("{}/redirect-to", base_url))
+        .query("url", format!("{}/headers", other_origin))
+        .header("Authorization", "Bearer secret")
+        .build();
+    let response = client.send(request).await.unwrap();
+    let body: HttpbinHeadersResponse =
+        serde_json::from_slice(&response.body.into_bytes().await.unwrap()).unwrap();
+    assert_eq!(body.headers.get("Authorization"), None);
+}
+
+#[tokio::test]
+async fn test_invalid_url() {
+    let client = HttpHyperClient::new();
+
+    let request = client.get("not-a-valid-url").build();
+    let result = client.send(request).await;
+
+    match result {
+        Err(HttpError::InvalidUrl(_)) => {}
+        Err(other) => panic!("Expected InvalidUrl error, got: {:?}", other),
+        Ok(_) => panic!("Expected error, got Ok"),
+    }
+}
+
+#[tokio::test]
+async fn test_connection_refused() {
+    let client = HttpHyperClient::new();
+
+    // Try to connect to a port that's not listening
+    let request = client.get("http://127.0.0.1:59999/get").build();
+    let result = client.send(request).await;
+
+    match result {
+        Err(e @ HttpError::Client(HyperError::Client(_))) => {
+            assert!(e.is_connect());
+            assert!(!e.is_timeout() && !e.is_tls() && !e.is_body() && !e.is_redirect());
+        }
+        Err(other) => panic!("Expected Client error, got: {:?}", other),
+        Ok(_) => panic!("Expected error, got Ok"),
+    }
+}
+
+#[tokio::test]
+async fn test_request_body_stream_error() {
+    /// A reader failing after the data it was created with.
+    struct FailingReader(&'static [u8]);
+
+    impl tokio::io::AsyncRead for FailingReader {
+        fn poll_read(
+            mut self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+            buf: &mut tokio::io::ReadBuf<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            if self.0.is_empty() {
+                return std::task::Poll::Ready(Err(std::io::Error::other("disk unplugged")));
+            }
+            buf.put_slice(self.0);
+            self.0 = &[];
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    // The server reads the request without ever answering it.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        use tokio::io::AsyncReadExt;
+
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buffer = [0; 1024];
+        while stream.read(&mut buffer).await.is_ok_and(|read| read > 0) {}
+    });
+
+    let client = HttpHyperClient::new();
+    let request = client
+        .post(url)
+        .body(body_from_reader(FailingReader(b"partial"), None))
+        .build();
+    let result = client.send(request).await;
+
+    match result {
+        Err(e) => {
+            assert!(e.is_body(), "Expected body error, got: {:?}", e);
+            assert!(!e.is_connect());
+        }
+        Ok(_) => panic!("Expected error, got Ok"),
+    }
+}
+
+#[tokio::test]
+async fn test_truncated_response_body() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buffer = [0; 1024];
+        let _ = stream.read(&mut buffer).await.unwrap();
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nabc")
+            .await
+            .unwrap();
+    });
+
+    let client = HttpHyperClient::new();
+    let response = client.send(client.get(url).build()).await.unwrap();
+
+    match response.body.into_bytes().await {
+        Err(e @ HyperError::Body(_)) => {
+            assert!(e.is_body());
+            // The error of hyper is kept as the source.
+            let source = std::error::Error::source(&e).unwrap();
+            assert!(source.is::<hyper::Error>());
+        }
+        Err(other) => panic!("Expected Body error, got: {:?}", other),
+        Ok(_) => panic!("Expected error, got Ok"),
+    }
+}
+
+/// Starts a local server answering the first request with `response`, then stalling until the
+/// client closes the connection.
+async fn start_stalling_server(response: &'static [u8]) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buffer = [0; 1024];
+        let _ = stream.read(&mut buffer).await.unwrap();
+        stream.write_all(response).await.unwrap();
+        while stream.read(&mut buffer).await.is_ok_and(|read| read > 0) {}
+    });
+    url
+}
+
+/// Cancels `token` after a short delay.
+fn cancel_later(token: &CancellationToken) {
+    let token = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        token.cancel();
+    });
+}
+
+#[tokio::test]
+async fn test_cancel_before_response() {
+    let url = start_stalling_server(b"").await;
+    let client = HttpHyperClient::new();
+
+    let token = CancellationToken::new();
+    cancel_later(&token);
+    let request = client.get(url).cancellation(token.child()).build();
+
+    match client.send(request).await {
+        Err(e @ HttpError::Cancelled) => {
+            assert!(e.is_cancelled());
+            assert!(!e.is_timeout());
+        }
+        Err(other) => panic!("Expected Cancelled error, got: {:?}", other),
+        Ok(_) => panic!("Expected error, got Ok"),
+    }
+}
+
+#[tokio::test]
+async fn test_cancel_while_reading_body() {
+    let url = start_stalling_server(b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nabc").await;
+    let client = HttpHyperClient::new();
+
+    let token = CancellationToken::new();
+    let request = client.get(url).cancellation(token.clone()).build();
+    let mut response = client.send(request).await.unwrap();
+    assert_eq!(
+        response.body.next_chunk().await.unwrap().as_deref(),
+        Some(&b"abc"[..])
+    );
+
+    cancel_later(&token);
+    match response.body.next_chunk().await {
+        Err(e @ HyperError::Cancelled) => {
+            assert!(e.is_cancelled());
+            assert!(HttpError::Client(e).is_cancelled());
+        }
+        Err(other) => panic!("Expected Cancelled error, got: {:?}", other),
+        Ok(_) => panic!("Expected error, got Ok"),
+    }
+}
+
+#[tokio::test]
+async fn test_cancellation_deadline() {
+    let url = start_stalling_server(b"").await;
+    let client = HttpHyperClient::new();
+
+    // The deadline of the parent token applies to requests made with its children, even if
+    // their own request timeout is longer
+    let parent =
+        CancellationToken::with_deadline(std::time::Instant::now() + Duration::from_millis(200));
+    let request = client
+        .get(url)
+        .timeout(Duration::from_secs(30))
+        .cancellation(parent.child())
+        .build();
+
+    match client.send(request).await {
+        Err(e @ HttpError::Timeout(TimeoutKind::Request)) => assert!(!e.is_cancelled()),
+        Err(other) => panic!("Expected request Timeout error, got: {:?}", other),
+        Ok(_) => panic!("Expected error, got Ok"),
+    }
+}
+
+#[tokio::test]
+async fn test_response_headers_multiple_and_binary_values() {
+    // httpbin cannot send non-UTF-8 header values, so use a raw local server
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buffer = [0; 1024];
+        let _ = stream.read(&mut buffer).await.unwrap();
+        stream
+            .write_all(
+                b"HTTP/1.1 200 OK\r\nSet-Cookie: a=1\r\nSet-Cookie: b=2\r\n\
+                  X-Latin1: caf\xe9\r\nContent-Type: text/plain\r\nContent-Length: 2\r\n\r\nok",
+            )
+            .await
+            .unwrap();
+    });
+
+    let client = HttpHyperClient::new();
+    let response = client.send(client.get(url).build()).await.unwrap();
+
+    assert_eq!(response.headers.get_all("set-cookie"), ["a=1", "b=2"]);
+    assert_eq!(
+        response.headers.get("X-Latin1").unwrap().as_bytes(),
+        b"caf\xe9"
+    );
+    assert!(response.headers.get("X-Latin1").unwrap().to_str().is_err());
+    assert_eq!(response.headers.content_type(), Some("text/plain"));
+    assert_eq!(response.headers.content_length(), Some(2));
+}
+
+#[tokio::test]
+async fn test_response_metadata() {
+    // A keep-alive server answering every request of a single connection
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buffer = [0; 1024];
+        while stream.read(&mut buffer).await.is_ok_and(|read| read > 0) {
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok")
+                .await
+                .unwrap();
+        }
+    });
+
+    let client = HttpHyperClient::new();
+    let url = format!("http://localhost:{}/", addr.port());
+
+    let response = client.send(client.get(&url).build()).await.unwrap();
+    response.body.into_bytes().await.unwrap();
+    let metadata = response.metadata;
+    assert_eq!(metadata.version, Some(HttpVersion::Http11));
+    assert_eq!(metadata.remote_addr, Some(addr));
+    assert_eq!(metadata.reused, Some(false));
+    assert!(metadata.timings.dns.is_some());
+    assert!(metadata.timings.connect.is_some());
+    assert_eq!(metadata.timings.tls, None);
+    assert!(metadata.timings.ttfb.unwrap() >= metadata.timings.connect.unwrap());
+
+    // The second request reuses the pooled connection
+    let response = client.send(client.get(&url).build()).await.unwrap();
+    let metadata = response.metadata;
+    assert_eq!(metadata.remote_addr, Some(addr));
+    assert_eq!(metadata.reused, Some(true));
+    assert_eq!(metadata.timings.dns, None);
+    assert_eq!(metadata.timings.connect, None);
+    assert!(metadata.timings.ttfb.is_some());
+}
+
+#[tokio::test]
+async fn test_post_form_pairs() {
+    let (_container, base_url) = start_httpbin().await;
+    let client = HttpHyperClient::new();
+
+    let request = client
+        .post(format!("{}/post", base_url))
+        .form([("grant_type", "password"), ("username", "jöhn & co=1")])
+        .build();
+    let response = client.send(request).await.unwrap();
+
+    assert!(response.is_success());
+
+    let body_bytes = response.body.into_bytes().await.unwrap();
+    let body: HttpbinResponse = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(body.form.get("grant_type"), Some(&"password".to_string()));
+    assert_eq!(body.form.get("username"), Some(&"jöhn & co=1".to_string()));
+    assert_eq!(
+        body.headers.get("Content-Type"),
+        Some(&"application/x-www-form-urlencoded".to_string())
+    );
+}
+
+#[cfg(feature = "json")]
+mod json_tests {
+    use super::*;
+
+    #[derive(Debug, serde::Serialize, Deserialize, PartialEq)]
+    struct TestPayload {
+        name: String,
+        value: i32,
+    }
+
+    #[tokio::test]
+    async fn test_json_response() {
+        let (_container, base_url) = start_httpbin().await;
+        let client = HttpHyperClient::new();
+
+        let request = client.get(format!("{}/get", base_url)).build();
+        let response = client.send(request).await.unwrap();
+
+        assert!(response.is_success());
+
+        let body: HttpbinResponse = response.body.json().await.unwrap();
+        assert!(body.url.contains("/get"));
+    }
+
+    #[tokio::test]
+    async fn test_post_json_body() {
+        let (_container, base_url) = start_httpbin().await;
+        let client = HttpHyperClient::new();
+
+        let payload = TestPayload {
+            name: "test".to_string(),
+            value: 42,
+        };
+
+        let request = client
             .post(format!("{}/post", base_url))
             .json(&payload)
             .unwrap()
@@ -381,3 +1500,720 @@ mod json_tests {
         assert_eq!(content_type, Some(&"application/json".to_string()));
     }
 }
+
+#[cfg(feature = "form")]
+mod form_tests {
+    use super::*;
+
+    #[derive(Debug, serde::Serialize, Deserialize, PartialEq)]
+    struct Credentials {
+        username: String,
+        scope: Option<String>,
+    }
+
+    #[tokio::test]
+    async fn test_post_form_serde() {
+        let (_container, base_url) = start_httpbin().await;
+        let client = HttpHyperClient::new();
+
+        let credentials = Credentials {
+            username: "john doe".to_string(),
+            scope: Some("read write".to_string()),
+        };
+        let request = client
+            .post(format!("{}/post", base_url))
+            .form_serde(&credentials)
+            .unwrap()
+            .build();
+        let response = client.send(request).await.unwrap();
+
+        assert!(response.is_success());
+
+        let body_bytes = response.body.into_bytes().await.unwrap();
+        let body: HttpbinResponse = serde_json::from_slice(&body_bytes).unwrap();
+        assert_eq!(body.form.get("username"), Some(&"john doe".to_string()));
+        assert_eq!(body.form.get("scope"), Some(&"read write".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_form_response() {
+        let (_container, base_url) = start_httpbin().await;
+        let client = HttpHyperClient::new();
+
+        // httpbin's /base64 endpoint decodes arbitrary content, used here to get a form response
+        let request = client
+            .get(format!(
+                "{}/base64/dXNlcm5hbWU9am9obitkb2Umc2NvcGU9cmVhZA==",
+                base_url
+            ))
+            .build();
+        let response = client.send(request).await.unwrap();
+
+        assert!(response.is_success());
+
+        let credentials: Credentials = response.body.form().await.unwrap();
+        assert_eq!(
+            credentials,
+            Credentials {
+                username: "john doe".to_string(),
+                scope: Some("read".to_string()),
+            }
+        );
+    }
+}
+
+#[cfg(feature = "query")]
+mod query_tests {
+    use super::*;
+    use http_client::ArrayFormat;
+
+    #[derive(serde::Serialize)]
+    struct Search {
+        q: String,
+        page: Option<u32>,
+        limit: Option<u32>,
+        tags: Vec<String>,
+        filter: Filter,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Filter {
+        min_price: f64,
+        in_stock: bool,
+    }
+
+    fn search() -> Search {
+        Search {
+            q: "rust & co".to_string(),
+            page: Some(2),
+            limit: None,
+            tags: vec!["a".to_string(), "b".to_string()],
+            filter: Filter {
+                min_price: 9.5,
+                in_stock: true,
+            },
+        }
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_query_serde_formats() {
+        let client = HttpHyperClient::new();
+
+        let request = client
+            .get("http://example.com")
+            .query("before", "1")
+            .query_serde(&search())
+            .unwrap()
+            .build();
+        assert_eq!(
+            request.query,
+            pairs(&[
+                ("before", "1"),
+                ("q", "rust & co"),
+                ("page", "2"),
+                ("tags", "a"),
+                ("tags", "b"),
+                ("filter[min_price]", "9.5"),
+                ("filter[in_stock]", "true"),
+            ])
+        );
+
+        let request = client
+            .get("http://example.com")
+            .query_serde_with(&search(), ArrayFormat::Brackets)
+            .unwrap()
+            .build();
+        assert!(
+            request
+                .query
+                .contains(&("tags[]".to_string(), "b".to_string()))
+        );
+
+        let request = client
+            .get("http://example.com")
+            .query_serde_with(&search(), ArrayFormat::Indices)
+            .unwrap()
+            .build();
+        assert!(
+            request
+                .query
+                .contains(&("tags[1]".to_string(), "b".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_query_serde_invalid_value() {
+        let client = HttpHyperClient::new();
+
+        assert!(client.get("http://example.com").query_serde(&42).is_err());
+        assert!(
+            client
+                .get("http://example.com")
+                .query_serde(&["a", "b"])
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_serde_request() {
+        let (_container, base_url) = start_httpbin().await;
+        let client = HttpHyperClient::new();
+
+        let request = client
+            .get(format!("{}/get", base_url))
+            .query_serde(&search())
+            .unwrap()
+            .build();
+        let response = client.send(request).await.unwrap();
+
+        assert!(response.is_success());
+
+        let body_bytes = response.body.into_bytes().await.unwrap();
+        let body: HttpbinResponse = serde_json::from_slice(&body_bytes).unwrap();
+        assert_eq!(body.args.get("q"), Some(&"rust & co".to_string()));
+        assert_eq!(body.args.get("filter[in_stock]"), Some(&"true".to_string()));
+        assert!(!body.args.contains_key("limit"));
+    }
+}
+
+#[cfg(feature = "decompress-gzip")]
+mod decompression_tests {
+    use super::*;
+    use std::io::Write;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// Starts a server answering a single request with the given head and body, and returns its
+    /// URL along with the request it received.
+    async fn start_server(
+        head: &'static str,
+        body: Vec<u8>,
+    ) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = [0; 1024];
+            let read = stream.read(&mut buffer).await.unwrap();
+            stream.write_all(head.as_bytes()).await.unwrap();
+            stream.write_all(&body).await.unwrap();
+            String::from_utf8_lossy(&buffer[..read]).to_lowercase()
+        });
+        (url, server)
+    }
+
+    #[tokio::test]
+    async fn test_gzip_response_decompressed() {
+        let compressed = gzip(b"hello, compressed world");
+        let head =
+            "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nTransfer-Encoding: chunked\r\n\r\n";
+        let mut body = format!("{:x}\r\n", compressed.len()).into_bytes();
+        body.extend_from_slice(&compressed);
+        body.extend_from_slice(b"\r\n0\r\n\r\n");
+        let (url, server) = start_server(head, body).await;
+
+        let client = HttpHyperClient::new();
+        let response = client.send(client.get(url).build()).await.unwrap();
+
+        assert!(!response.headers.contains("content-encoding"));
+        let body = response.body.into_bytes().await.unwrap();
+        assert_eq!(body, b"hello, compressed world");
+        let request = server.await.unwrap();
+        let accept_encoding = request
+            .lines()
+            .find_map(|line| line.strip_prefix("accept-encoding: "))
+            .unwrap();
+        assert!(
+            accept_encoding
+                .split(", ")
+                .any(|encoding| encoding == "gzip")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_decompression_opt_out() {
+        let compressed = gzip(b"raw please");
+        let head = Box::leak(
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\n\r\n",
+                compressed.len()
+            )
+            .into_boxed_str(),
+        );
+        let (url, server) = start_server(head, compressed.clone()).await;
+
+        let client = HttpHyperClient::new();
+        let request = client.get(url).decompress(false).build();
+        let response = client.send(request).await.unwrap();
+
+        assert_eq!(response.headers.get("content-encoding").unwrap(), "gzip");
+        assert_eq!(response.body.into_bytes().await.unwrap(), compressed);
+        assert!(!server.await.unwrap().contains("accept-encoding"));
+    }
+
+    #[tokio::test]
+    async fn test_custom_accept_encoding_kept() {
+        let head = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n";
+        let (url, server) = start_server(head, b"ok".to_vec()).await;
+
+        let client = HttpHyperClient::new();
+        let request = client
+            .get(url)
+            .header("Accept-Encoding", "identity")
+            .build();
+        client.send(request).await.unwrap();
+
+        let request = server.await.unwrap();
+        assert!(request.contains("accept-encoding: identity"));
+        assert!(!request.contains("gzip"));
+    }
+
+    #[tokio::test]
+    async fn test_empty_compressed_body() {
+        let head = "HTTP/1.1 204 No Content\r\nContent-Encoding: gzip\r\n\r\n";
+        let (url, _server) = start_server(head, Vec::new()).await;
+
+        let client = HttpHyperClient::new();
+        let response = client.send(client.get(url).build()).await.unwrap();
+
+        assert_eq!(response.status, 204);
+        assert!(response.body.into_bytes().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_bodiless_response_headers_kept() {
+        // Nothing is decoded for HEAD requests, so the size of the encoded body is kept
+        let head = "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: 42\r\n\r\n";
+        let (url, _server) = start_server(head, Vec::new()).await;
+
+        let client = HttpHyperClient::new();
+        let response = client.send(client.head(url).build()).await.unwrap();
+
+        assert_eq!(response.headers.get("content-encoding").unwrap(), "gzip");
+        assert_eq!(response.headers.get("content-length").unwrap(), "42");
+        assert!(response.body.into_bytes().await.unwrap().is_empty());
+
+        // Neither is it for responses without a body
+        let head =
+            "HTTP/1.1 304 Not Modified\r\nContent-Encoding: gzip\r\nContent-Length: 42\r\n\r\n";
+        let (url, _server) = start_server(head, Vec::new()).await;
+
+        let response = client.send(client.get(url).build()).await.unwrap();
+
+        assert_eq!(response.status, 304);
+        assert_eq!(response.headers.get("content-encoding").unwrap(), "gzip");
+        assert_eq!(response.headers.get("content-length").unwrap(), "42");
+        assert!(response.body.into_bytes().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_empty_chunked_compressed_body() {
+        let head =
+            "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nTransfer-Encoding: chunked\r\n\r\n";
+        let (url, _server) = start_server(head, b"0\r\n\r\n".to_vec()).await;
+
+        let client = HttpHyperClient::new();
+        let response = client.send(client.get(url).build()).await.unwrap();
+
+        assert!(response.body.into_bytes().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_compressed_body() {
+        let head = "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: 9\r\n\r\n";
+        let (url, _server) = start_server(head, b"not gzip!".to_vec()).await;
+
+        let client = HttpHyperClient::new();
+        let response = client.send(client.get(url).build()).await.unwrap();
+
+        assert!(matches!(
+            response.body.into_bytes().await,
+            Err(HyperError::Body(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_gzip_httpbin() {
+        let (_container, base_url) = start_httpbin().await;
+        let client = HttpHyperClient::new();
+
+        let response = client
+            .send(client.get(format!("{}/gzip", base_url)).build())
+            .await
+            .unwrap();
+
+        let body: serde_json::Value =
+            serde_json::from_slice(&response.body.into_bytes().await.unwrap()).unwrap();
+        assert_eq!(body["gzipped"], true);
+    }
+
+    #[cfg(feature = "decompress-deflate")]
+    #[tokio::test]
+    async fn test_deflate_httpbin() {
+        let (_container, base_url) = start_httpbin().await;
+        let client = HttpHyperClient::new();
+
+        let response = client
+            .send(client.get(format!("{}/deflate", base_url)).build())
+            .await
+            .unwrap();
+
+        let body: serde_json::Value =
+            serde_json::from_slice(&response.body.into_bytes().await.unwrap()).unwrap();
+        assert_eq!(body["deflated"], true);
+    }
+
+    #[cfg(feature = "decompress-brotli")]
+    #[tokio::test]
+    async fn test_brotli_httpbin() {
+        let (_container, base_url) = start_httpbin().await;
+        let client = HttpHyperClient::new();
+
+        let response = client
+            .send(client.get(format!("{}/brotli", base_url)).build())
+            .await
+            .unwrap();
+
+        let body: serde_json::Value =
+            serde_json::from_slice(&response.body.into_bytes().await.unwrap()).unwrap();
+        assert_eq!(body["brotli"], true);
+    }
+}
+
+#[cfg(feature = "gzip")]
+mod compression_tests {
+    use super::*;
+    use std::io::Read;
+
+    use http_client::Compression;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Starts a server answering a single request, and returns its URL along with the headers and
+    /// body of the request it received.
+    async fn start_server() -> (String, tokio::task::JoinHandle<(String, Vec<u8>)>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            let (head, length) = loop {
+                let read = stream.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+                if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                    let head = String::from_utf8_lossy(&request[..end]).to_lowercase();
+                    let length = head
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length: "))
+                        .map_or(0, |length| length.parse().unwrap());
+                    request.drain(..end + 4);
+                    break (head, length);
+                }
+            };
+            while request.len() < length {
+                let read = stream.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 204 No Content\r\n\r\n")
+                .await
+                .unwrap();
+            (head, request)
+        });
+        (url, server)
+    }
+
+    #[tokio::test]
+    async fn test_gzip_request_body() {
+        let (url, server) = start_server().await;
+        let payload = "telemetry ".repeat(1000);
+
+        let client = HttpHyperClient::new();
+        let request = client
+            .post(url)
+            .body(payload.clone())
+            .compress(Compression::Gzip)
+            .build();
+        assert_eq!(request.headers.get("content-encoding").unwrap(), "gzip");
+
+        // The compressed body is buffered, so the request can be sent again (e.g. when retrying).
+        assert!(request.body.as_ref().unwrap().is_replayable());
+        client.send(request.clone()).await.unwrap();
+
+        let (head, body) = server.await.unwrap();
+        assert!(head.contains("content-encoding: gzip"));
+        assert!(body.len() < payload.len());
+        let mut decoded = String::new();
+        flate2::read::GzDecoder::new(&body[..])
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, payload);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[tokio::test]
+    async fn test_zstd_request_body() {
+        let (url, server) = start_server().await;
+        let payload = "telemetry ".repeat(1000);
+
+        let client = HttpHyperClient::new();
+        let request = client
+            .post(url)
+            .compress(Compression::Zstd)
+            .body(payload.clone())
+            .build();
+        client.send(request).await.unwrap();
+
+        let (head, body) = server.await.unwrap();
+        assert!(head.contains("content-encoding: zstd"));
+        assert_eq!(zstd::decode_all(&body[..]).unwrap(), payload.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_compression_skips_encoded_and_empty_bodies() {
+        let client = HttpHyperClient::new();
+
+        let request = client
+            .post("http://example.com")
+            .header("Content-Encoding", "br")
+            .body("already compressed")
+            .compress(Compression::Gzip)
+            .build();
+        assert_eq!(request.headers.get("content-encoding").unwrap(), "br");
+        assert_eq!(
+            request.body.unwrap().as_bytes(),
+            Some(&b"already compressed"[..])
+        );
+
+        let request = client
+            .get("http://example.com")
+            .compress(Compression::Gzip)
+            .build();
+        assert!(!request.headers.contains("content-encoding"));
+    }
+}
+
+#[cfg(feature = "cookies")]
+mod cookie_tests {
+    use super::*;
+    use http_client::{CookieJar, PublicSuffixList};
+    use http_client_hyper::HyperClientBuilder;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Starts a server answering requests with the given responses, one per connection, and
+    /// returns its URL along with the (lowercase) heads of the requests it received.
+    async fn start_server(
+        responses: Vec<&'static str>,
+    ) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = [0; 4096];
+                let read = stream.read(&mut buffer).await.unwrap();
+                requests.push(String::from_utf8_lossy(&buffer[..read]).to_lowercase());
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+        (url, server)
+    }
+
+    fn cookie_header(request: &str) -> Option<&str> {
+        request
+            .lines()
+            .find_map(|line| line.strip_prefix("cookie: "))
+    }
+
+    #[tokio::test]
+    async fn test_cookie_jar_attributes() {
+        let (url, server) = start_server(vec![
+            "HTTP/1.1 200 OK\r\nConnection: close\r\n\
+             Set-Cookie: session=abc; Path=/\r\n\
+             Set-Cookie: admin=1; Path=/admin\r\n\
+             Set-Cookie: gone=1; Max-Age=0\r\n\
+             Set-Cookie: other=1; Domain=example.com\r\n\
+             Content-Length: 0\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: 0\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: 0\r\n\r\n",
+        ])
+        .await;
+
+        let jar = CookieJar::new();
+        let client = HyperClientBuilder::new()
+            .cookie_jar(jar.clone())
+            .build_http();
+        for path in ["/login", "/profile", "/admin/users"] {
+            let request = client.get(format!("{url}{path}")).build();
+            client.send(request).await.unwrap();
+        }
+
+        let requests = server.await.unwrap();
+        assert_eq!(cookie_header(&requests[0]), None);
+        assert_eq!(cookie_header(&requests[1]), Some("session=abc"));
+        let admin_cookies = cookie_header(&requests[2]).unwrap();
+        assert!(admin_cookies.contains("session=abc"));
+        assert!(admin_cookies.contains("admin=1"));
+        assert!(!admin_cookies.contains("gone"));
+        assert!(!admin_cookies.contains("other"));
+    }
+
+    #[tokio::test]
+    async fn test_cookie_jar_on_redirect() {
+        let (url, server) = start_server(vec![
+            "HTTP/1.1 302 Found\r\nConnection: close\r\nLocation: /home\r\n\
+             Set-Cookie: session=abc\r\nContent-Length: 0\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: 0\r\n\r\n",
+        ])
+        .await;
+
+        let jar = CookieJar::new();
+        let client = HyperClientBuilder::new()
+            .cookie_jar(jar.clone())
+            .build_http();
+        let response = client
+            .send(client.post(format!("{url}/login")).build())
+            .await
+            .unwrap();
+        assert_eq!(response.status, 200);
+
+        let requests = server.await.unwrap();
+        assert_eq!(cookie_header(&requests[1]), Some("session=abc"));
+        assert_eq!(
+            jar.cookie_header(&format!("{url}/anything")).as_deref(),
+            Some("session=abc")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cookie_jar_explicit_header_and_middleware() {
+        let (url, server) = start_server(vec![
+            "HTTP/1.1 200 OK\r\nConnection: close\r\n\
+             Set-Cookie: session=abc\r\nContent-Length: 0\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: 0\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: 0\r\n\r\n",
+        ])
+        .await;
+
+        let jar = CookieJar::new();
+        let client = HttpHyperClient::new().with_middleware(jar.clone());
+        client.send(client.get(&url).build()).await.unwrap();
+        client.send(client.get(&url).build()).await.unwrap();
+        let request = client.get(&url).header("Cookie", "mine=1").build();
+        client.send(request).await.unwrap();
+
+        let requests = server.await.unwrap();
+        assert_eq!(cookie_header(&requests[1]), Some("session=abc"));
+        assert_eq!(cookie_header(&requests[2]), Some("mine=1"));
+    }
+
+    #[test]
+    fn test_cookie_jar_secure() {
+        let jar = CookieJar::new();
+        jar.add_cookie("token=1; Secure", "https://example.com")
+            .unwrap();
+        jar.add_cookie("theme=dark", "https://example.com").unwrap();
+
+        let header = jar.cookie_header("https://example.com/").unwrap();
+        let mut cookies: Vec<_> = header.split("; ").collect();
+        cookies.sort();
+        assert_eq!(cookies, ["theme=dark", "token=1"]);
+        assert_eq!(
+            jar.cookie_header("http://example.com/").as_deref(),
+            Some("theme=dark")
+        );
+
+        jar.clear();
+        assert_eq!(jar.cookie_header("https://example.com/"), None);
+    }
+
+    #[test]
+    fn test_cookie_jar_public_suffix() {
+        let suffixes = || {
+            "// ===BEGIN ICANN DOMAINS===\ncom\nuk\nco.uk\n"
+                .parse::<PublicSuffixList>()
+                .unwrap()
+        };
+
+        // Without a list, nothing prevents setting cookies for a public suffix
+        let jar = CookieJar::new();
+        jar.add_cookie("tracker=1; Domain=co.uk", "http://evil.co.uk")
+            .unwrap();
+        assert!(jar.cookie_header("http://bank.co.uk/").is_some());
+
+        let jar = CookieJar::new().with_public_suffix_list(suffixes());
+        assert!(
+            jar.add_cookie("tracker=1; Domain=co.uk", "http://evil.co.uk")
+                .is_err()
+        );
+        jar.add_cookie("own=1; Domain=evil.co.uk", "http://evil.co.uk")
+            .unwrap();
+        assert_eq!(jar.cookie_header("http://bank.co.uk/"), None);
+        assert_eq!(
+            jar.cookie_header("http://www.evil.co.uk/").as_deref(),
+            Some("own=1")
+        );
+
+        // Jars loaded from a file can be given a list as well
+        let loaded = CookieJar::load_json(&b"[]"[..])
+            .unwrap()
+            .with_public_suffix_list(suffixes());
+        assert!(
+            loaded
+                .add_cookie("tracker=1; Domain=com", "http://evil.com")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_cookie_jar_persistence() {
+        let path = std::env::temp_dir().join(format!("cookies-{}.json", std::process::id()));
+        let jar = CookieJar::load_file(&path).unwrap();
+        jar.add_cookie("persistent=1; Max-Age=3600", "http://example.com")
+            .unwrap();
+        jar.add_cookie("session=1", "http://example.com").unwrap();
+        jar.save_file(&path).unwrap();
+
+        let loaded = CookieJar::load_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        // Session cookies are not persisted.
+        assert_eq!(
+            loaded.cookie_header("http://example.com/").as_deref(),
+            Some("persistent=1")
+        );
+        assert_eq!(loaded.cookie_header("http://other.com/"), None);
+    }
+
+    #[tokio::test]
+    async fn test_cookies_httpbin() {
+        let (_container, base_url) = start_httpbin().await;
+        let jar = CookieJar::new();
+        let client = HyperClientBuilder::new().cookie_jar(jar).build_http();
+
+        // Sets the cookie and redirects to `/cookies`, which echoes the cookies sent.
+        let request = client
+            .get(format!("{}/cookies/set", base_url))
+            .query("flavor", "chocolate")
+            .build();
+        let response = client.send(request).await.unwrap();
+
+        let body: serde_json::Value =
+            serde_json::from_slice(&response.body.into_bytes().await.unwrap()).unwrap();
+        assert_eq!(body["cookies"]["flavor"], "chocolate");
+    }
+}
@@ -0,0 +1,248 @@
+#![cfg(feature = "rustls")]
+
+use std::sync::Arc;
+
+use http_client::{HttpClient, HttpError, HttpVersion, StatusCode};
+use http_client_hyper::{HyperClientBuilder, HyperError, TlsConfig};
+use rcgen::{BasicConstraints, Certificate, CertificateParams, IsCa, KeyPair};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+
+/// A certificate authority issuing certificates for the tests.
+struct TestCa {
+    certificate: Certificate,
+    key: KeyPair,
+}
+
+impl TestCa {
+    fn new() -> Self {
+        let key = KeyPair::generate().unwrap();
+        let mut params = CertificateParams::new(Vec::new()).unwrap();
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let certificate = params.self_signed(&key).unwrap();
+        Self { certificate, key }
+    }
+
+    /// Issues a certificate for the given names, returning it along with its private key.
+    fn issue(&self, names: &[&str]) -> (Certificate, KeyPair) {
+        let key = KeyPair::generate().unwrap();
+        let names = names
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        let certificate = CertificateParams::new(names)
+            .unwrap()
+            .signed_by(&key, &self.certificate, &self.key)
+            .unwrap();
+        (certificate, key)
+    }
+
+    fn pem(&self) -> String {
+        self.certificate.pem()
+    }
+}
+
+/// Starts a TLS server answering every request with `200 OK`, returning its base URL.
+///
+/// If `client_ca` is given, clients must authenticate with a certificate issued by it.
+async fn start_tls_server(
+    certificate: &Certificate,
+    key: &KeyPair,
+    client_ca: Option<&TestCa>,
+) -> String {
+    let chain = vec![certificate.der().clone()];
+    let key = PrivateKeyDer::Pkcs8(key.serialize_der().into());
+
+    let builder = rustls::ServerConfig::builder();
+    let builder = match client_ca {
+        Some(ca) => {
+            let mut roots = rustls::RootCertStore::empty();
+            roots.add(ca.certificate.der().clone()).unwrap();
+            let verifier = WebPkiClientVerifier::builder(Arc::new(roots))
+                .build()
+                .unwrap();
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+    let config = builder.with_single_cert(chain, key).unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                let Ok(mut stream) = acceptor.accept(stream).await else {
+                    return;
+                };
+                let mut request = Vec::new();
+                let mut buffer = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    match stream.read(&mut buffer).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => request.extend_from_slice(&buffer[..n]),
+                    }
+                }
+                let _ = stream
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok",
+                    )
+                    .await;
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+
+    format!("https://localhost:{port}")
+}
+
+/// Returns a TLS configuration only trusting the given certificate authority.
+fn trusting(ca: &TestCa) -> TlsConfig {
+    TlsConfig::new()
+        .native_roots(false)
+        .add_root_certificates_pem(ca.pem().as_bytes())
+        .unwrap()
+}
+
+async fn get_status(tls_config: TlsConfig, url: &str) -> Result<StatusCode, HttpError<HyperError>> {
+    let client = HyperClientBuilder::new()
+        .tls_config(tls_config)
+        .build_https()
+        .unwrap();
+    let request = client.get(url).build();
+    client.send(request).await.map(|response| response.status)
+}
+
+#[tokio::test]
+async fn test_tls_custom_root() {
+    let ca = TestCa::new();
+    let (certificate, key) = ca.issue(&["localhost"]);
+    let url = start_tls_server(&certificate, &key, None).await;
+
+    let status = get_status(trusting(&ca), &url).await.unwrap();
+    assert_eq!(status, 200);
+}
+
+#[tokio::test]
+async fn test_tls_response_metadata() {
+    let ca = TestCa::new();
+    let (certificate, key) = ca.issue(&["localhost"]);
+    let url = start_tls_server(&certificate, &key, None).await;
+
+    let client = HyperClientBuilder::new()
+        .tls_config(trusting(&ca))
+        .build_https()
+        .unwrap();
+    let response = client.send(client.get(url).build()).await.unwrap();
+
+    let metadata = response.metadata;
+    assert_eq!(metadata.version, Some(HttpVersion::Http11));
+    assert_eq!(metadata.reused, Some(false));
+    assert!(metadata.remote_addr.unwrap().ip().is_loopback());
+    let timings = metadata.timings;
+    assert!(timings.dns.is_some() && timings.connect.is_some() && timings.tls.is_some());
+    assert!(timings.ttfb.unwrap() >= timings.tls.unwrap());
+}
+
+#[tokio::test]
+async fn test_tls_untrusted_root() {
+    let ca = TestCa::new();
+    let (certificate, key) = ca.issue(&["localhost"]);
+    let url = start_tls_server(&certificate, &key, None).await;
+
+    let result = get_status(trusting(&TestCa::new()), &url).await;
+    match result {
+        Err(e @ HttpError::Client(_)) => assert!(e.is_connect() && e.is_tls()),
+        other => panic!("Expected TLS error, got: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_tls_root_certificate_der() {
+    let ca = TestCa::new();
+    let (certificate, key) = ca.issue(&["localhost"]);
+    let url = start_tls_server(&certificate, &key, None).await;
+
+    let tls_config = TlsConfig::new()
+        .native_roots(false)
+        .add_root_certificate(ca.certificate.der().clone());
+    let status = get_status(tls_config, &url).await.unwrap();
+    assert_eq!(status, 200);
+}
+
+#[tokio::test]
+async fn test_tls_client_auth() {
+    let ca = TestCa::new();
+    let (certificate, key) = ca.issue(&["localhost"]);
+    let url = start_tls_server(&certificate, &key, Some(&ca)).await;
+
+    // Without a client certificate, the server rejects the handshake
+    let result = get_status(trusting(&ca), &url).await;
+    assert!(matches!(result, Err(HttpError::Client(_))));
+
+    let (client_certificate, client_key) = ca.issue(&["client"]);
+    let tls_config = trusting(&ca)
+        .client_auth_pem(
+            client_certificate.pem().as_bytes(),
+            client_key.serialize_pem().as_bytes(),
+        )
+        .unwrap();
+    let status = get_status(tls_config, &url).await.unwrap();
+    assert_eq!(status, 200);
+}
+
+#[tokio::test]
+async fn test_tls_certificate_pinning() {
+    let ca = TestCa::new();
+    let (certificate, key) = ca.issue(&["localhost"]);
+    let url = start_tls_server(&certificate, &key, None).await;
+
+    let fingerprint = ring::digest::digest(&ring::digest::SHA256, certificate.der());
+    let pin: [u8; 32] = fingerprint.as_ref().try_into().unwrap();
+    let status = get_status(trusting(&ca).pin_certificate_sha256(pin), &url)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+
+    let result = get_status(trusting(&ca).pin_certificate_sha256([0; 32]), &url).await;
+    assert!(matches!(result, Err(HttpError::Client(_))));
+}
+
+#[tokio::test]
+async fn test_tls_from_rustls() {
+    let ca = TestCa::new();
+    let (certificate, key) = ca.issue(&["localhost"]);
+    let url = start_tls_server(&certificate, &key, None).await;
+
+    let mut roots = rustls::RootCertStore::empty();
+    roots
+        .add(CertificateDer::from(ca.certificate.der().to_vec()))
+        .unwrap();
+    let config = rustls::ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let status = get_status(TlsConfig::from_rustls(config), &url)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+}
+
+#[test]
+fn test_tls_invalid_configuration() {
+    let result = TlsConfig::new().add_root_certificates_pem(b"not a certificate");
+    assert!(matches!(result, Err(HyperError::Pem(_))));
+
+    let result = TlsConfig::new().add_root_certificates_pem_file("does/not/exist.pem");
+    assert!(matches!(result, Err(HyperError::Pem(_))));
+
+    let result = HyperClientBuilder::new()
+        .tls_config(TlsConfig::new().native_roots(false))
+        .build_https();
+    assert!(matches!(result, Err(HyperError::Tls(_))));
+}
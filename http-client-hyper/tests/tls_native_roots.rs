@@ -0,0 +1,41 @@
+#![cfg(feature = "rustls")]
+
+//! Kept in its own test binary since it changes the environment of the whole process.
+
+use http_client_hyper::{HyperClientBuilder, HyperError, TlsConfig};
+use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+
+#[test]
+fn test_tls_missing_native_roots() {
+    // Make rustls-native-certs look for the native roots where there are none, like in minimal
+    // containers. There is no other test in this binary, so no thread reads the environment.
+    unsafe {
+        std::env::set_var("SSL_CERT_FILE", "does/not/exist.pem");
+        std::env::set_var("SSL_CERT_DIR", "does/not/exist");
+    }
+
+    // Custom roots are enough, even though native roots are enabled
+    let key = KeyPair::generate().unwrap();
+    let mut params = CertificateParams::new(Vec::new()).unwrap();
+    params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    let ca = params.self_signed(&key).unwrap();
+    let tls_config = TlsConfig::new()
+        .add_root_certificates_pem(ca.pem().as_bytes())
+        .unwrap();
+    let result = HyperClientBuilder::new()
+        .tls_config(tls_config)
+        .build_https();
+    assert!(result.is_ok());
+
+    // Without any root, the failure to load native roots is reported
+    let result = HyperClientBuilder::new()
+        .tls_config(TlsConfig::new())
+        .build_https();
+    match result {
+        Err(HyperError::Tls(e)) => {
+            assert!(e.to_string().contains("native root certificates"), "{e}");
+        }
+        Err(other) => panic!("Expected Tls error, got: {:?}", other),
+        Ok(_) => panic!("Expected error, got Ok"),
+    }
+}
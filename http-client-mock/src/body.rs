@@ -0,0 +1,35 @@
+use std::collections::VecDeque;
+
+use http_client::{Bytes, ResponseBody};
+
+use crate::error::MockError;
+
+/// The body of a response returned by [`MockClient`](crate::MockClient).
+#[derive(Debug, Clone, Default)]
+pub struct MockBody {
+    chunks: VecDeque<Bytes>,
+}
+
+impl MockBody {
+    pub(crate) fn new(chunks: impl IntoIterator<Item = Bytes>) -> Self {
+        Self {
+            chunks: chunks.into_iter().collect(),
+        }
+    }
+}
+
+impl ResponseBody for MockBody {
+    type Error = MockError;
+
+    async fn into_bytes(mut self) -> Result<Vec<u8>, Self::Error> {
+        let mut bytes = Vec::new();
+        while let Some(chunk) = self.next_chunk().await? {
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(bytes)
+    }
+
+    async fn next_chunk(&mut self) -> Result<Option<Bytes>, Self::Error> {
+        Ok(self.chunks.pop_front())
+    }
+}
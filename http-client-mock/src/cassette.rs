@@ -0,0 +1,490 @@
+use core::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use http_client::{
+    BoxError, Bytes, ClassifyError, HeaderMap, HttpClient, HttpError, HttpRequest, HttpResponse,
+    ResponseBody, ResponseMetadata,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::body::MockBody;
+use crate::client::check_cancellation;
+
+/// The value recorded in place of redacted headers.
+const REDACTED: &str = "[REDACTED]";
+
+/// Whether a [`CassetteClient`] records or replays interactions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Sends requests through the inner client, and records them to the cassette, overwriting it.
+    Record,
+    /// Answers requests from the cassette, without using the inner client.
+    Replay,
+    /// Replays the cassette if it exists, and records it otherwise.
+    Auto,
+}
+
+/// A part of requests compared to find the recorded interaction to replay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatchOn {
+    /// The request method.
+    Method,
+    /// The request URL, without its query parameters.
+    Url,
+    /// The query parameters, in any order.
+    Query,
+    /// The values of the given header.
+    ///
+    /// Redacted headers are compared after redaction, so only their presence is checked.
+    Header(String),
+    /// The request body.
+    Body,
+}
+
+/// An [`HttpClient`] wrapper recording requests and responses to a cassette file, and replaying
+/// them later on without network access.
+///
+/// Cassettes are JSON files holding the interactions in the order they were recorded. When
+/// replaying, each request is answered by the first interaction that matches it (see
+/// [`CassetteClient::match_on`]) and was not replayed yet.
+///
+/// Response bodies are read entirely before being returned, in both modes. Streaming request
+/// bodies are not recorded.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use http_client::HttpClient;
+/// use http_client_mock::{CassetteClient, CassetteMode, MockClient};
+///
+/// # async fn run() {
+/// # let inner = MockClient::new();
+/// let client = CassetteClient::new(inner, "tests/cassettes/users.json", CassetteMode::Auto)
+///     .unwrap()
+///     .redact_header("X-Api-Key");
+///
+/// let request = client.get("https://api.example.com/users").build();
+/// let response = client.send(request).await.unwrap();
+/// # }
+/// ```
+pub struct CassetteClient<C> {
+    inner: C,
+    path: PathBuf,
+    mode: CassetteMode,
+    match_on: Vec<MatchOn>,
+    redacted: Vec<String>,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    cassette: Cassette,
+    replayed: Vec<bool>,
+}
+
+impl<C: HttpClient> CassetteClient<C> {
+    /// Wraps the given client, recording to or replaying from the cassette at `path`.
+    ///
+    /// Fails if the cassette must be replayed but cannot be read.
+    pub fn new(
+        inner: C,
+        path: impl Into<PathBuf>,
+        mode: CassetteMode,
+    ) -> Result<Self, CassetteError<C::Error>> {
+        let path = path.into();
+        let mode = match mode {
+            CassetteMode::Auto if path.exists() => CassetteMode::Replay,
+            CassetteMode::Auto => CassetteMode::Record,
+            mode => mode,
+        };
+
+        let mut state = State::default();
+        if mode == CassetteMode::Replay {
+            let cassette: Cassette = serde_json::from_slice(&std::fs::read(&path)?)?;
+            state.replayed = vec![false; cassette.interactions.len()];
+            state.cassette = cassette;
+        }
+
+        Ok(Self {
+            inner,
+            path,
+            mode,
+            match_on: vec![MatchOn::Method, MatchOn::Url, MatchOn::Query],
+            redacted: [
+                "authorization",
+                "proxy-authorization",
+                "cookie",
+                "set-cookie",
+            ]
+            .map(String::from)
+            .to_vec(),
+            state: Mutex::new(state),
+        })
+    }
+}
+
+impl<C> CassetteClient<C> {
+    /// Sets the parts of requests compared to find the interaction to replay.
+    ///
+    /// Defaults to the method, URL and query parameters.
+    pub fn match_on(mut self, rules: impl IntoIterator<Item = MatchOn>) -> Self {
+        self.match_on = rules.into_iter().collect();
+        self
+    }
+
+    /// Redacts the given header in recorded requests and responses.
+    ///
+    /// `Authorization`, `Proxy-Authorization`, `Cookie` and `Set-Cookie` are redacted by default.
+    pub fn redact_header(mut self, name: impl Into<String>) -> Self {
+        self.redacted.push(name.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Returns the mode of this client, `Auto` being resolved to the actual mode.
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// Returns the path of the cassette.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns a reference to the wrapped client.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn headers(&self, headers: &HeaderMap) -> Vec<(String, String)> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if self.redacted.iter().any(|redacted| redacted == name) {
+                    REDACTED.to_string()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes()).into_owned()
+                };
+                (name.to_string(), value)
+            })
+            .collect()
+    }
+
+    fn record_request(&self, request: &HttpRequest) -> RecordedRequest {
+        RecordedRequest {
+            method: request.method.to_string(),
+            url: request.url.clone(),
+            query: request.query.clone(),
+            headers: self.headers(&request.headers),
+            body: request
+                .body
+                .as_ref()
+                .and_then(|body| body.as_bytes())
+                .map(RecordedBody::new),
+        }
+    }
+
+    fn matches(&self, recorded: &RecordedRequest, request: &RecordedRequest) -> bool {
+        self.match_on.iter().all(|rule| match rule {
+            MatchOn::Method => recorded.method == request.method,
+            MatchOn::Url => recorded.url == request.url,
+            MatchOn::Query => {
+                let mut recorded = recorded.query.clone();
+                let mut query = request.query.clone();
+                recorded.sort();
+                query.sort();
+                recorded == query
+            }
+            MatchOn::Header(name) => recorded.header_values(name) == request.header_values(name),
+            MatchOn::Body => recorded.body == request.body,
+        })
+    }
+
+    fn replay<E>(
+        &self,
+        request: &HttpRequest,
+    ) -> Result<HttpResponse<MockBody>, HttpError<CassetteError<E>>> {
+        check_cancellation(request)?;
+
+        let recorded_request = self.record_request(request);
+        let mut state = self.state();
+        let State { cassette, replayed } = &mut *state;
+
+        let position = cassette
+            .interactions
+            .iter()
+            .zip(replayed.iter())
+            .position(|(interaction, replayed)| {
+                !replayed && self.matches(&interaction.request, &recorded_request)
+            })
+            .ok_or_else(|| {
+                HttpError::Client(CassetteError::NoInteraction(format!(
+                    "{} {}",
+                    request.method, request.url
+                )))
+            })?;
+        replayed[position] = true;
+
+        let response = &cassette.interactions[position].response;
+        let body = response.body.to_bytes().map_err(HttpError::Client)?;
+        Ok(HttpResponse {
+            status: response.status.into(),
+            headers: response.headers.iter().cloned().collect(),
+            body: MockBody::new([body]),
+            url: response.url.clone(),
+            redirects: response.redirects.clone(),
+            metadata: ResponseMetadata::default(),
+        })
+    }
+
+    fn save<E>(&self, interaction: Interaction) -> Result<(), CassetteError<E>> {
+        let mut state = self.state();
+        state.cassette.interactions.push(interaction);
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_vec_pretty(&state.cassette)?)?;
+        Ok(())
+    }
+}
+
+impl<C> HttpClient for CassetteClient<C>
+where
+    C: HttpClient + Sync,
+    C::Body: Send,
+    <C::Body as ResponseBody>::Error: Into<BoxError>,
+{
+    type Body = MockBody;
+    type Error = CassetteError<C::Error>;
+
+    async fn send(
+        &self,
+        request: HttpRequest,
+    ) -> Result<HttpResponse<Self::Body>, HttpError<Self::Error>> {
+        let error_for_status = request.error_for_status;
+        let response = if self.mode == CassetteMode::Replay {
+            self.replay(&request)?
+        } else {
+            self.record(request).await?
+        };
+
+        if error_for_status {
+            return response.error_for_status().await;
+        }
+        Ok(response)
+    }
+}
+
+impl<C> CassetteClient<C>
+where
+    C: HttpClient + Sync,
+    C::Body: Send,
+    <C::Body as ResponseBody>::Error: Into<BoxError>,
+{
+    /// Sends the request through the inner client, and records it along with its response.
+    async fn record(
+        &self,
+        mut request: HttpRequest,
+    ) -> Result<HttpResponse<MockBody>, HttpError<CassetteError<C::Error>>> {
+        // Error responses are recorded like any other, and turned into errors when returned
+        request.error_for_status = false;
+
+        let recorded_request = self.record_request(&request);
+        let response = self
+            .inner
+            .send(request)
+            .await
+            .map_err(|e| e.map_client(CassetteError::Client))?;
+
+        let body = response
+            .body
+            .into_bytes()
+            .await
+            .map_err(|e| HttpError::Client(CassetteError::Body(e.into())))?;
+        let interaction = Interaction {
+            request: recorded_request,
+            response: RecordedResponse {
+                status: response.status.as_u16(),
+                headers: self.headers(&response.headers),
+                body: RecordedBody::new(&body),
+                url: response.url.clone(),
+                redirects: response.redirects.clone(),
+            },
+        };
+        self.save(interaction).map_err(HttpError::Client)?;
+
+        Ok(HttpResponse {
+            status: response.status,
+            headers: response.headers,
+            body: MockBody::new([Bytes::from(body)]),
+            url: response.url,
+            redirects: response.redirects,
+            metadata: response.metadata,
+        })
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    url: String,
+    #[serde(default)]
+    query: Vec<(String, String)>,
+    #[serde(default)]
+    headers: Vec<(String, String)>,
+    #[serde(default)]
+    body: Option<RecordedBody>,
+}
+
+impl RecordedRequest {
+    fn header_values(&self, name: &str) -> Vec<&str> {
+        self.headers
+            .iter()
+            .filter(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    #[serde(default)]
+    headers: Vec<(String, String)>,
+    body: RecordedBody,
+    url: String,
+    #[serde(default)]
+    redirects: Vec<String>,
+}
+
+/// A recorded body, stored as text when possible to keep cassettes readable.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RecordedBody {
+    Text(String),
+    Base64(String),
+}
+
+impl RecordedBody {
+    fn new(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => RecordedBody::Text(text.to_string()),
+            Err(_) => RecordedBody::Base64(BASE64.encode(bytes)),
+        }
+    }
+
+    fn to_bytes<E>(&self) -> Result<Bytes, CassetteError<E>> {
+        match self {
+            RecordedBody::Text(text) => Ok(Bytes::from(text.clone())),
+            RecordedBody::Base64(encoded) => BASE64
+                .decode(encoded)
+                .map(Bytes::from)
+                .map_err(|e| CassetteError::Io(io::Error::new(io::ErrorKind::InvalidData, e))),
+        }
+    }
+}
+
+/// Errors returned by [`CassetteClient`].
+#[derive(Debug)]
+pub enum CassetteError<E> {
+    /// Error from the inner client.
+    Client(E),
+    /// Error reading the response body of the inner client.
+    Body(BoxError),
+    /// No recorded interaction matched the request, which is described in the message.
+    NoInteraction(String),
+    /// Error reading or writing the cassette file.
+    Io(io::Error),
+    /// Invalid cassette file.
+    Json(serde_json::Error),
+}
+
+impl<E: fmt::Display> fmt::Display for CassetteError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CassetteError::Client(e) => write!(f, "client error: {e}"),
+            CassetteError::Body(e) => write!(f, "body error: {e}"),
+            CassetteError::NoInteraction(request) => {
+                write!(f, "no recorded interaction for {request}")
+            }
+            CassetteError::Io(e) => write!(f, "cassette io error: {e}"),
+            CassetteError::Json(e) => write!(f, "cassette json error: {e}"),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for CassetteError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CassetteError::Client(e) => Some(e),
+            CassetteError::Body(e) => Some(e.as_ref()),
+            CassetteError::NoInteraction(_) => None,
+            CassetteError::Io(e) => Some(e),
+            CassetteError::Json(e) => Some(e),
+        }
+    }
+}
+
+// Errors of the inner client keep their classification, so that e.g. a `RetryClient` wrapping a
+// recording cassette still retries connection errors.
+impl<E: ClassifyError> ClassifyError for CassetteError<E> {
+    fn is_connect(&self) -> bool {
+        matches!(self, CassetteError::Client(e) if e.is_connect())
+    }
+
+    fn is_timeout(&self) -> bool {
+        matches!(self, CassetteError::Client(e) if e.is_timeout())
+    }
+
+    fn is_tls(&self) -> bool {
+        matches!(self, CassetteError::Client(e) if e.is_tls())
+    }
+
+    fn is_body(&self) -> bool {
+        match self {
+            CassetteError::Client(e) => e.is_body(),
+            CassetteError::Body(_) => true,
+            _ => false,
+        }
+    }
+
+    fn is_redirect(&self) -> bool {
+        matches!(self, CassetteError::Client(e) if e.is_redirect())
+    }
+
+    fn is_cancelled(&self) -> bool {
+        matches!(self, CassetteError::Client(e) if e.is_cancelled())
+    }
+}
+
+impl<E> From<io::Error> for CassetteError<E> {
+    fn from(err: io::Error) -> Self {
+        CassetteError::Io(err)
+    }
+}
+
+impl<E> From<serde_json::Error> for CassetteError<E> {
+    fn from(err: serde_json::Error) -> Self {
+        CassetteError::Json(err)
+    }
+}
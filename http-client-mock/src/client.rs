@@ -0,0 +1,149 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+
+use http_client::{HttpClient, HttpError, HttpRequest, HttpResponse, TimeoutKind};
+
+use crate::body::MockBody;
+use crate::error::{MockError, VerifyError};
+use crate::mock::{Expected, Mock};
+
+/// An [`HttpClient`] answering requests with the [`Mock`]s registered on it.
+///
+/// Each request is answered by the first registered mock that matches it. Requests matching no
+/// mock fail with [`MockError::Unexpected`], and are reported by [`MockClient::verify`].
+///
+/// Clones of a client share their mocks and recorded requests, so a clone can be kept around to
+/// verify a client that was moved into the code under test.
+#[derive(Debug, Clone, Default)]
+pub struct MockClient {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    mocks: Vec<(Mock, usize)>,
+    requests: Vec<HttpRequest>,
+    unexpected: Vec<String>,
+}
+
+impl MockClient {
+    /// Creates a client without any mock.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a mock.
+    pub fn mock(&self, mock: Mock) {
+        self.state().mocks.push((mock, 0));
+    }
+
+    /// Returns all the requests received so far, in order.
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.state().requests.clone()
+    }
+
+    /// Checks that every mock was called the expected number of times, and that no unexpected
+    /// request was received.
+    pub fn verify(&self) -> Result<(), VerifyError> {
+        let state = self.state();
+        let mut failures = Vec::new();
+        for (mock, calls) in &state.mocks {
+            match mock.expected() {
+                Expected::AtLeast(times) if *calls < times => failures.push(format!(
+                    "{mock}: expected at least {times} calls, got {calls}"
+                )),
+                Expected::Exactly(times) if *calls != times => {
+                    failures.push(format!("{mock}: expected {times} calls, got {calls}"))
+                }
+                _ => {}
+            }
+        }
+        for request in &state.unexpected {
+            failures.push(format!("unexpected request: {request}"));
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(VerifyError::new(failures))
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl HttpClient for MockClient {
+    type Body = MockBody;
+    type Error = MockError;
+
+    async fn send(
+        &self,
+        request: HttpRequest,
+    ) -> Result<HttpResponse<Self::Body>, HttpError<Self::Error>> {
+        // The responder is called once the state is unlocked, so that it can use the client
+        // (e.g. to inspect the requests received so far).
+        let mock = {
+            let mut state = self.state();
+            state.requests.push(request.clone());
+
+            // Requests failing this way don't count as mock calls
+            check_cancellation(&request)?;
+
+            let mock = state.mocks.iter_mut().find(|(mock, calls)| {
+                let exhausted =
+                    matches!(mock.expected(), Expected::Exactly(times) if *calls >= times);
+                !exhausted && mock.matches(&request)
+            });
+            match mock {
+                Some((mock, calls)) => {
+                    *calls += 1;
+                    mock.clone()
+                }
+                None => {
+                    let description = describe(&request);
+                    state.unexpected.push(description.clone());
+                    return Err(HttpError::Client(MockError::Unexpected(description)));
+                }
+            }
+        };
+        let response = mock.respond_to(&request)?;
+
+        if request.error_for_status {
+            return response.error_for_status().await;
+        }
+        Ok(response)
+    }
+}
+
+/// Fails if the request was cancelled, or its deadline was reached, before being sent.
+///
+/// Mocked responses are immediate, so this is the only point where requests can be cancelled.
+pub(crate) fn check_cancellation<E>(request: &HttpRequest) -> Result<(), HttpError<E>> {
+    let Some(token) = &request.cancellation else {
+        return Ok(());
+    };
+    if token.is_cancelled() {
+        return Err(HttpError::Cancelled);
+    }
+    if token
+        .deadline()
+        .is_some_and(|deadline| deadline <= Instant::now())
+    {
+        return Err(HttpError::Timeout(TimeoutKind::Request));
+    }
+    Ok(())
+}
+
+/// Describes a request in error messages.
+fn describe(request: &HttpRequest) -> String {
+    let mut description = format!("{} {}", request.method, request.url);
+    for (i, (key, value)) in request.query.iter().enumerate() {
+        description.push(if i == 0 { '?' } else { '&' });
+        description.push_str(&format!("{key}={value}"));
+    }
+    description
+}
@@ -0,0 +1,87 @@
+use core::fmt;
+
+use http_client::ClassifyError;
+
+/// Errors returned by [`MockClient`](crate::MockClient).
+#[derive(Debug)]
+pub enum MockError {
+    /// No mock matched the request, which is described in the message.
+    Unexpected(String),
+    /// JSON deserialization error.
+    #[cfg(feature = "json")]
+    Json(serde_json::Error),
+    /// URL-encoded form deserialization error.
+    #[cfg(feature = "form")]
+    Form(serde_urlencoded::de::Error),
+}
+
+impl fmt::Display for MockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MockError::Unexpected(request) => write!(f, "unexpected request: {request}"),
+            #[cfg(feature = "json")]
+            MockError::Json(e) => write!(f, "json error: {e}"),
+            #[cfg(feature = "form")]
+            MockError::Form(e) => write!(f, "form error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for MockError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MockError::Unexpected(_) => None,
+            #[cfg(feature = "json")]
+            MockError::Json(e) => Some(e),
+            #[cfg(feature = "form")]
+            MockError::Form(e) => Some(e),
+        }
+    }
+}
+
+// Mocked requests never reach the network, so their errors fall into none of the categories.
+impl ClassifyError for MockError {}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Error> for MockError {
+    fn from(err: serde_json::Error) -> Self {
+        MockError::Json(err)
+    }
+}
+
+#[cfg(feature = "form")]
+impl From<serde_urlencoded::de::Error> for MockError {
+    fn from(err: serde_urlencoded::de::Error) -> Self {
+        MockError::Form(err)
+    }
+}
+
+/// Error returned by [`MockClient::verify`](crate::MockClient::verify), listing every unmet
+/// expectation and unexpected request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyError {
+    failures: Vec<String>,
+}
+
+impl VerifyError {
+    pub(crate) fn new(failures: Vec<String>) -> Self {
+        Self { failures }
+    }
+
+    /// Returns the description of each failure.
+    pub fn failures(&self) -> &[String] {
+        &self.failures
+    }
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "mock verification failed:")?;
+        for failure in &self.failures {
+            write!(f, "\n- {failure}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for VerifyError {}
@@ -0,0 +1,52 @@
+//! Mock implementation of the `http-client` crate, for unit testing code using an `HttpClient`.
+//!
+//! This crate provides [`MockClient`], a client answering requests with canned responses instead
+//! of sending them over the network. Each [`Mock`] matches requests on their method, URL, query,
+//! headers and body, and can expect to be called a given number of times, which is checked by
+//! [`MockClient::verify`].
+//!
+//! # Features
+//!
+//! - `json` - Enables matching and responding with JSON bodies.
+//! - `form` - Enables reading URL-encoded form responses.
+//! - `cassette` - Enables [`CassetteClient`], which records interactions with a real client to
+//!   replay them later on.
+//!
+//! # Example
+//!
+//! ```rust
+//! use http_client::{HttpClient, HttpMethod, ResponseBody};
+//! use http_client_mock::{Mock, MockClient, MockResponse};
+//!
+//! # async fn run() {
+//! let client = MockClient::new();
+//! client.mock(
+//!     Mock::new()
+//!         .method(HttpMethod::Get)
+//!         .url("http://example.com/users")
+//!         .query("page", "2")
+//!         .respond(MockResponse::new(200).body("[]"))
+//!         .times(1),
+//! );
+//!
+//! let request = client.get("http://example.com/users").query("page", "2").build();
+//! let response = client.send(request).await.unwrap();
+//! assert_eq!(response.body.into_bytes().await.unwrap(), b"[]");
+//!
+//! client.verify().unwrap();
+//! # }
+//! ```
+
+mod body;
+#[cfg(feature = "cassette")]
+mod cassette;
+mod client;
+mod error;
+mod mock;
+
+pub use body::MockBody;
+#[cfg(feature = "cassette")]
+pub use cassette::{CassetteClient, CassetteError, CassetteMode, MatchOn};
+pub use client::MockClient;
+pub use error::{MockError, VerifyError};
+pub use mock::{Mock, MockResponse};
@@ -0,0 +1,282 @@
+use core::fmt;
+use std::sync::Arc;
+
+use http_client::{
+    Bytes, HeaderMap, HeaderValue, HttpError, HttpMethod, HttpRequest, HttpResponse,
+    ResponseMetadata,
+};
+
+use crate::body::MockBody;
+use crate::error::MockError;
+
+type Matcher = Arc<dyn Fn(&HttpRequest) -> bool + Send + Sync>;
+type Responder =
+    Arc<dyn Fn(&HttpRequest) -> Result<MockResponse, HttpError<MockError>> + Send + Sync>;
+
+/// A canned answer to the requests matching some conditions, registered with
+/// [`MockClient::mock`](crate::MockClient::mock).
+///
+/// A mock without any condition matches every request. By default, a mock answers with an empty
+/// `200 OK` response, and is expected to be called at least once.
+#[derive(Clone)]
+pub struct Mock {
+    method: Option<HttpMethod>,
+    url: Option<String>,
+    query: Vec<(String, String)>,
+    headers: Vec<(String, HeaderValue)>,
+    body: Option<Bytes>,
+    matchers: Vec<(String, Matcher)>,
+    responder: Responder,
+    expected: Expected,
+}
+
+/// The number of times a mock is expected to be called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Expected {
+    AtLeast(usize),
+    Exactly(usize),
+}
+
+impl Mock {
+    /// Creates a mock matching every request.
+    pub fn new() -> Self {
+        Self {
+            method: None,
+            url: None,
+            query: Vec::new(),
+            headers: Vec::new(),
+            body: None,
+            matchers: Vec::new(),
+            responder: Arc::new(|_| Ok(MockResponse::new(200))),
+            expected: Expected::AtLeast(1),
+        }
+    }
+
+    /// Only matches requests with the given method.
+    pub fn method(mut self, method: HttpMethod) -> Self {
+        self.method = Some(method);
+        self
+    }
+
+    /// Only matches requests with the given URL, without its query parameters.
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    /// Only matches requests with the given query parameter.
+    ///
+    /// Requests may have other query parameters as well.
+    pub fn query(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query.push((key.into(), value.into()));
+        self
+    }
+
+    /// Only matches requests with the given header value.
+    ///
+    /// Requests may have other headers, or other values for this header, as well.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<HeaderValue>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Only matches requests with the given (buffered) body.
+    pub fn body(mut self, body: impl Into<Bytes>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// Only matches requests whose body is JSON equal to the given value.
+    ///
+    /// Object keys may be in any order.
+    #[cfg(feature = "json")]
+    pub fn json<T: serde::Serialize>(self, value: &T) -> Result<Self, serde_json::Error> {
+        let expected = serde_json::to_value(value)?;
+        let description = format!("json body {expected}");
+        Ok(self.matching(description, move |request| {
+            request
+                .body
+                .as_ref()
+                .and_then(|body| body.as_bytes())
+                .and_then(|body| serde_json::from_slice::<serde_json::Value>(body).ok())
+                .is_some_and(|body| body == expected)
+        }))
+    }
+
+    /// Only matches requests for which the given function returns true.
+    ///
+    /// The description is used to report unmet expectations. The function is called while the
+    /// [`MockClient`](crate::MockClient) is locked, so it must not use the client itself.
+    pub fn matching<F>(mut self, description: impl Into<String>, f: F) -> Self
+    where
+        F: Fn(&HttpRequest) -> bool + Send + Sync + 'static,
+    {
+        self.matchers.push((description.into(), Arc::new(f)));
+        self
+    }
+
+    /// Answers matching requests with the given response.
+    pub fn respond(self, response: MockResponse) -> Self {
+        self.respond_with(move |_| Ok(response.clone()))
+    }
+
+    /// Answers matching requests with the result of the given function, which may also fail to
+    /// simulate errors like timeouts.
+    ///
+    /// The function may use the [`MockClient`](crate::MockClient) the mock is registered on, e.g.
+    /// to answer according to the requests received so far.
+    pub fn respond_with<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&HttpRequest) -> Result<MockResponse, HttpError<MockError>> + Send + Sync + 'static,
+    {
+        self.responder = Arc::new(f);
+        self
+    }
+
+    /// Expects the mock to be called exactly `times` times.
+    ///
+    /// Once called that many times, the mock no longer matches requests, which lets further
+    /// requests be answered by the next matching mocks.
+    pub fn times(mut self, times: usize) -> Self {
+        self.expected = Expected::Exactly(times);
+        self
+    }
+
+    /// Expects the mock to be called at least `times` times.
+    pub fn at_least(mut self, times: usize) -> Self {
+        self.expected = Expected::AtLeast(times);
+        self
+    }
+
+    /// Expects the mock to be called any number of times, including never.
+    pub fn optional(self) -> Self {
+        self.at_least(0)
+    }
+
+    pub(crate) fn expected(&self) -> Expected {
+        self.expected
+    }
+
+    /// Returns true if the mock matches the given request.
+    pub(crate) fn matches(&self, request: &HttpRequest) -> bool {
+        self.method
+            .as_ref()
+            .is_none_or(|method| *method == request.method)
+            && self.url.as_ref().is_none_or(|url| *url == request.url)
+            && self.query.iter().all(|pair| request.query.contains(pair))
+            && self
+                .headers
+                .iter()
+                .all(|(name, value)| request.headers.get_all(name).contains(value))
+            && self.body.as_ref().is_none_or(|body| {
+                let actual = request
+                    .body
+                    .as_ref()
+                    .map_or(Some(&[][..]), |b| b.as_bytes());
+                actual == Some(&body[..])
+            })
+            && self.matchers.iter().all(|(_, matcher)| matcher(request))
+    }
+
+    /// Builds the response to the given request.
+    pub(crate) fn respond_to(
+        &self,
+        request: &HttpRequest,
+    ) -> Result<HttpResponse<MockBody>, HttpError<MockError>> {
+        let response = (self.responder)(request)?;
+        Ok(HttpResponse {
+            status: response.status.into(),
+            headers: response.headers,
+            body: MockBody::new(response.chunks),
+            url: request.url.clone(),
+            redirects: Vec::new(),
+            metadata: ResponseMetadata::default(),
+        })
+    }
+}
+
+impl Default for Mock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for Mock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.method {
+            Some(method) => write!(f, "{method}")?,
+            None => write!(f, "*")?,
+        }
+        write!(f, " {}", self.url.as_deref().unwrap_or("*"))?;
+        for (key, value) in &self.query {
+            write!(f, " query {key}={value}")?;
+        }
+        for (name, value) in &self.headers {
+            write!(f, " header {name}: {value:?}")?;
+        }
+        if let Some(body) = &self.body {
+            write!(f, " body {body:?}")?;
+        }
+        for (description, _) in &self.matchers {
+            write!(f, " {description}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Mock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Mock")
+            .field(&format_args!("{self}"))
+            .finish()
+    }
+}
+
+/// A canned response returned by a [`Mock`].
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: u16,
+    headers: HeaderMap,
+    chunks: Vec<Bytes>,
+}
+
+impl MockResponse {
+    /// Creates an empty response with the given status code.
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            headers: HeaderMap::new(),
+            chunks: Vec::new(),
+        }
+    }
+
+    /// Adds a response header.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<HeaderValue>) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Sets the response body.
+    pub fn body(self, body: impl Into<Bytes>) -> Self {
+        self.chunks([body.into()])
+    }
+
+    /// Sets the response body as a sequence of chunks, as read by
+    /// [`ResponseBody::next_chunk`](http_client::ResponseBody::next_chunk).
+    pub fn chunks(mut self, chunks: impl IntoIterator<Item = Bytes>) -> Self {
+        self.chunks = chunks
+            .into_iter()
+            .filter(|chunk| !chunk.is_empty())
+            .collect();
+        self
+    }
+
+    /// Sets the response body as JSON, serializing the provided value.
+    ///
+    /// This also sets the `Content-Type` header to `application/json`.
+    #[cfg(feature = "json")]
+    pub fn json<T: serde::Serialize>(mut self, value: &T) -> Result<Self, serde_json::Error> {
+        self.headers.insert("Content-Type", "application/json");
+        Ok(self.body(serde_json::to_vec(value)?))
+    }
+}
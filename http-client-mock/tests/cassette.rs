@@ -0,0 +1,320 @@
+#![cfg(feature = "cassette")]
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use http_client::{
+    CancellationToken, ClassifyError, HttpClient, HttpError, HttpRequest, HttpResponse,
+    ResponseBody, RetryClient, StatusCode, TimeoutKind, Timer,
+};
+use http_client_mock::{
+    CassetteClient, CassetteError, CassetteMode, MatchOn, Mock, MockBody, MockClient, MockResponse,
+};
+
+/// Returns a cassette path unique to the test, removing any previous cassette.
+fn cassette_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir()
+        .join(format!("http-client-mock-{}", std::process::id()))
+        .join(format!("{name}.json"));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// Returns a client recording the interactions with a mock server to the given cassette.
+fn recording_client(path: &PathBuf) -> CassetteClient<MockClient> {
+    let server = MockClient::new();
+    server.mock(
+        Mock::new()
+            .url("http://example.com/users")
+            .respond(
+                MockResponse::new(200)
+                    .header("Content-Type", "application/json")
+                    .header("Set-Cookie", "session=secret")
+                    .body(r#"[{"name":"Alice"}]"#),
+            )
+            .times(1),
+    );
+    server.mock(
+        Mock::new()
+            .url("http://example.com/avatar")
+            .respond(MockResponse::new(200).body(&b"\x89PNG\x00\xff"[..]))
+            .times(1),
+    );
+    CassetteClient::new(server, path, CassetteMode::Record).unwrap()
+}
+
+#[tokio::test]
+async fn test_record_and_replay() {
+    let path = cassette_path("record_and_replay");
+
+    let client = recording_client(&path);
+    let request = client
+        .get("http://example.com/users")
+        .header("Authorization", "Bearer secret")
+        .build();
+    let response = client.send(request).await.unwrap();
+    assert_eq!(
+        response.headers.get("set-cookie").unwrap(),
+        "session=secret"
+    );
+    assert_eq!(
+        response.body.into_bytes().await.unwrap(),
+        br#"[{"name":"Alice"}]"#
+    );
+    let request = client.get("http://example.com/avatar").build();
+    client.send(request).await.unwrap();
+    client.inner().verify().unwrap();
+
+    // Replaying does not use the inner client at all
+    let client = CassetteClient::new(MockClient::new(), &path, CassetteMode::Replay).unwrap();
+    let request = client.get("http://example.com/avatar").build();
+    let response = client.send(request).await.unwrap();
+    assert_eq!(
+        response.body.into_bytes().await.unwrap(),
+        b"\x89PNG\x00\xff"
+    );
+
+    let request = client.get("http://example.com/users").build();
+    let response = client.send(request).await.unwrap();
+    assert_eq!(response.status, 200);
+    assert_eq!(response.url, "http://example.com/users");
+    assert_eq!(response.headers.content_type(), Some("application/json"));
+    assert_eq!(
+        response.body.into_bytes().await.unwrap(),
+        br#"[{"name":"Alice"}]"#
+    );
+
+    // Each interaction is only replayed once
+    let request = client.get("http://example.com/users").build();
+    let result = client.send(request).await;
+    assert!(matches!(
+        result,
+        Err(HttpError::Client(CassetteError::NoInteraction(_)))
+    ));
+}
+
+#[tokio::test]
+async fn test_header_redaction() {
+    let path = cassette_path("header_redaction");
+
+    let client = recording_client(&path).redact_header("X-Api-Key");
+    let request = client
+        .get("http://example.com/users")
+        .header("Authorization", "Bearer secret")
+        .header("X-Api-Key", "secret")
+        .build();
+    client.send(request).await.unwrap();
+
+    let cassette = std::fs::read_to_string(&path).unwrap();
+    assert!(!cassette.contains("secret"));
+    assert!(cassette.contains("[REDACTED]"));
+}
+
+#[tokio::test]
+async fn test_matching_rules() {
+    let path = cassette_path("matching_rules");
+
+    let client = recording_client(&path);
+    let request = client
+        .post("http://example.com/users")
+        .query("a", "1")
+        .query("b", "2")
+        .header("X-Version", "2")
+        .body("payload")
+        .build();
+    client.send(request).await.unwrap();
+
+    // By default, only the method, URL and query parameters (in any order) are compared
+    let client = CassetteClient::new(MockClient::new(), &path, CassetteMode::Replay).unwrap();
+    let request = client
+        .post("http://example.com/users")
+        .query("b", "2")
+        .query("a", "1")
+        .body("other payload")
+        .build();
+    assert!(client.send(request).await.is_ok());
+
+    let client = CassetteClient::new(MockClient::new(), &path, CassetteMode::Replay)
+        .unwrap()
+        .match_on([
+            MatchOn::Url,
+            MatchOn::Body,
+            MatchOn::Header("x-version".into()),
+        ]);
+    let request = client
+        .post("http://example.com/users")
+        .header("X-Version", "2")
+        .body("other payload")
+        .build();
+    assert!(client.send(request).await.is_err());
+
+    let request = client
+        .put("http://example.com/users")
+        .header("X-Version", "2")
+        .body("payload")
+        .build();
+    assert!(client.send(request).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_auto_mode() {
+    let path = cassette_path("auto_mode");
+
+    let client = CassetteClient::new(MockClient::new(), &path, CassetteMode::Auto).unwrap();
+    assert_eq!(client.mode(), CassetteMode::Record);
+
+    let client = recording_client(&path);
+    let request = client.get("http://example.com/users").build();
+    client.send(request).await.unwrap();
+
+    let client = CassetteClient::new(MockClient::new(), &path, CassetteMode::Auto).unwrap();
+    assert_eq!(client.mode(), CassetteMode::Replay);
+}
+
+#[tokio::test]
+async fn test_error_for_status() {
+    let path = cassette_path("error_for_status");
+
+    // Error responses are recorded even though the request fails
+    let server = MockClient::new();
+    server.mock(
+        Mock::new()
+            .url("http://example.com/missing")
+            .respond(MockResponse::new(404).body("not found"))
+            .times(1),
+    );
+    let client = CassetteClient::new(server, &path, CassetteMode::Record).unwrap();
+    let request = client
+        .get("http://example.com/missing")
+        .error_for_status(true)
+        .build();
+    let err = client.send(request).await.unwrap_err();
+    assert_eq!(err.status(), Some(StatusCode::NOT_FOUND));
+    client.inner().verify().unwrap();
+
+    let client = CassetteClient::new(MockClient::new(), &path, CassetteMode::Replay).unwrap();
+    let request = client
+        .get("http://example.com/missing")
+        .error_for_status(true)
+        .build();
+    match client.send(request).await {
+        Err(HttpError::Status(e)) => assert_eq!(e.body().as_ref(), b"not found"),
+        Err(other) => panic!("Expected Status error, got: {:?}", other),
+        Ok(_) => panic!("Expected error, got Ok"),
+    }
+
+    let client = CassetteClient::new(MockClient::new(), &path, CassetteMode::Replay).unwrap();
+    let request = client.get("http://example.com/missing").build();
+    assert_eq!(client.send(request).await.unwrap().status, 404);
+}
+
+#[tokio::test]
+async fn test_replay_cancellation() {
+    let path = cassette_path("replay_cancellation");
+    let client = recording_client(&path);
+    let request = client.get("http://example.com/users").build();
+    client.send(request).await.unwrap();
+
+    let client = CassetteClient::new(MockClient::new(), &path, CassetteMode::Replay).unwrap();
+    let token = CancellationToken::new();
+    token.cancel();
+    let request = client
+        .get("http://example.com/users")
+        .cancellation(token.child())
+        .build();
+    assert!(client.send(request).await.unwrap_err().is_cancelled());
+
+    let expired = CancellationToken::with_deadline(Instant::now());
+    let request = client
+        .get("http://example.com/users")
+        .cancellation(expired)
+        .build();
+    let err = client.send(request).await.unwrap_err();
+    assert!(matches!(err, HttpError::Timeout(TimeoutKind::Request)));
+
+    // Cancelled requests don't consume the interaction
+    let request = client.get("http://example.com/users").build();
+    assert_eq!(client.send(request).await.unwrap().status, 200);
+}
+
+/// Connection error classified as such.
+#[derive(Debug)]
+struct Refused;
+
+impl fmt::Display for Refused {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("connection refused")
+    }
+}
+
+impl std::error::Error for Refused {}
+
+impl ClassifyError for Refused {
+    fn is_connect(&self) -> bool {
+        true
+    }
+}
+
+/// Client refusing the first connection, then answering with a mock client.
+struct FlakyClient {
+    attempts: AtomicUsize,
+    inner: MockClient,
+}
+
+impl HttpClient for FlakyClient {
+    type Body = MockBody;
+    type Error = Refused;
+
+    async fn send(
+        &self,
+        request: HttpRequest,
+    ) -> Result<HttpResponse<Self::Body>, HttpError<Self::Error>> {
+        if self.attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+            return Err(HttpError::Client(Refused));
+        }
+        self.inner
+            .send(request)
+            .await
+            .map_err(|e| e.map_client(|_| Refused))
+    }
+}
+
+struct NoopTimer;
+
+impl Timer for NoopTimer {
+    async fn sleep(&self, _duration: Duration) {}
+}
+
+#[tokio::test]
+async fn test_inner_error_classification() {
+    let path = cassette_path("inner_error_classification");
+    let server = MockClient::new();
+    server.mock(Mock::new().respond(MockResponse::new(200)).times(1));
+    let flaky = FlakyClient {
+        attempts: AtomicUsize::new(0),
+        inner: server,
+    };
+
+    // Connection errors of the inner client are retried through the cassette
+    let client = RetryClient::new(
+        CassetteClient::new(flaky, &path, CassetteMode::Record).unwrap(),
+        NoopTimer,
+    );
+    let request = client.get("http://example.com").build();
+    assert_eq!(client.send(request).await.unwrap().status, 200);
+    assert_eq!(client.inner().inner().attempts.load(Ordering::SeqCst), 2);
+
+    let err: HttpError<CassetteError<Refused>> = HttpError::Client(CassetteError::Client(Refused));
+    assert!(err.is_connect());
+    assert!(!err.is_body());
+    assert!(std::error::Error::source(&err).is_some());
+}
+
+#[test]
+fn test_missing_cassette() {
+    let path = cassette_path("missing");
+    let result = CassetteClient::new(MockClient::new(), &path, CassetteMode::Replay);
+    assert!(matches!(result, Err(CassetteError::Io(_))));
+}
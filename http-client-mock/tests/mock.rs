@@ -0,0 +1,618 @@
+use http_client::{
+    CancellationToken, Defaults, HttpClient, HttpError, HttpMethod, ResponseBody, RetryClient,
+    RetryPolicy, StatusCode, StatusError, TimeoutKind, Timer, Token, TokenAuth, TokenProvider,
+};
+use http_client_mock::{Mock, MockClient, MockError, MockResponse};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Timer that does not wait, to test retries without slowing tests down.
+struct NoopTimer;
+
+impl Timer for NoopTimer {
+    async fn sleep(&self, _duration: Duration) {}
+}
+
+#[tokio::test]
+async fn test_canned_response() {
+    let client = MockClient::new();
+    client.mock(
+        Mock::new()
+            .method(HttpMethod::Get)
+            .url("http://example.com/users")
+            .respond(
+                MockResponse::new(200)
+                    .header("Content-Type", "text/plain")
+                    .body("hello"),
+            ),
+    );
+
+    let request = client.get("http://example.com/users").build();
+    let response = client.send(request).await.unwrap();
+
+    assert_eq!(response.status, 200);
+    assert_eq!(response.url, "http://example.com/users");
+    assert_eq!(response.headers.content_type(), Some("text/plain"));
+    assert_eq!(response.body.into_bytes().await.unwrap(), b"hello");
+    client.verify().unwrap();
+}
+
+#[tokio::test]
+async fn test_matchers() {
+    let client = MockClient::new();
+    client.mock(
+        Mock::new()
+            .method(HttpMethod::Post)
+            .url("http://example.com/items")
+            .query("page", "2")
+            .header("authorization", "Bearer token")
+            .body("payload")
+            .respond(MockResponse::new(201)),
+    );
+    client.mock(Mock::new().respond(MockResponse::new(404)).optional());
+
+    let matching = client
+        .post("http://example.com/items")
+        .query("page", "2")
+        .query("size", "10")
+        .header("Authorization", "Bearer token")
+        .body("payload");
+
+    let response = client.send(matching.clone().build()).await.unwrap();
+    assert_eq!(response.status, 201);
+
+    let mut wrong_query = matching.clone().build();
+    wrong_query.query.retain(|(key, _)| key != "page");
+    assert_eq!(client.send(wrong_query).await.unwrap().status, 404);
+
+    let wrong_body = matching.clone().body("other").build();
+    assert_eq!(client.send(wrong_body).await.unwrap().status, 404);
+
+    let mut wrong_header = matching.clone().build();
+    wrong_header.headers.remove("authorization");
+    assert_eq!(client.send(wrong_header).await.unwrap().status, 404);
+
+    let wrong_method = client.get("http://example.com/items").build();
+    assert_eq!(client.send(wrong_method).await.unwrap().status, 404);
+
+    client.verify().unwrap();
+    assert_eq!(client.requests().len(), 5);
+}
+
+#[tokio::test]
+async fn test_custom_matcher() {
+    let client = MockClient::new();
+    client.mock(
+        Mock::new()
+            .matching("url ending with .json", |request| {
+                request.url.ends_with(".json")
+            })
+            .respond(MockResponse::new(200)),
+    );
+
+    let request = client.get("http://example.com/data.json").build();
+    assert_eq!(client.send(request).await.unwrap().status, 200);
+    client.verify().unwrap();
+}
+
+#[tokio::test]
+async fn test_responder_using_client() {
+    let client = MockClient::new();
+    let inspected = client.clone();
+    client.mock(Mock::new().respond_with(move |_| {
+        // Answers according to the requests received so far, and the state of the mocks
+        let status = 200 + inspected.requests().len() as u16;
+        assert!(inspected.verify().is_ok());
+        Ok(MockResponse::new(status))
+    }));
+
+    let request = client.get("http://example.com").build();
+    assert_eq!(client.send(request.clone()).await.unwrap().status, 201);
+    assert_eq!(client.send(request).await.unwrap().status, 202);
+}
+
+#[tokio::test]
+async fn test_unexpected_request() {
+    let client = MockClient::new();
+    client.mock(Mock::new().url("http://example.com/a").optional());
+
+    let request = client.get("http://example.com/b").query("x", "1").build();
+    let result = client.send(request).await;
+    match result {
+        Err(HttpError::Client(MockError::Unexpected(request))) => {
+            assert_eq!(request, "GET http://example.com/b?x=1")
+        }
+        other => panic!("Expected unexpected request error, got: {:?}", other),
+    }
+
+    let error = client.verify().unwrap_err();
+    assert_eq!(
+        error.failures(),
+        ["unexpected request: GET http://example.com/b?x=1"]
+    );
+}
+
+#[tokio::test]
+async fn test_call_count_expectations() {
+    let client = MockClient::new();
+    client.mock(Mock::new().url("http://example.com/once").times(1));
+    client.mock(Mock::new().url("http://example.com/never").times(0));
+    client.mock(Mock::new().url("http://example.com/twice").at_least(2));
+
+    let request = client.get("http://example.com/twice").build();
+    client.send(request).await.unwrap();
+
+    let error = client.verify().unwrap_err();
+    assert_eq!(
+        error.failures(),
+        [
+            "* http://example.com/once: expected 1 calls, got 0",
+            "* http://example.com/twice: expected at least 2 calls, got 1",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_exhausted_mocks_fall_through() {
+    let client = MockClient::new();
+    client.mock(Mock::new().respond(MockResponse::new(503)).times(2));
+    client.mock(Mock::new().respond(MockResponse::new(200)).times(1));
+
+    let statuses = [503, 503, 200];
+    for status in statuses {
+        let request = client.get("http://example.com").build();
+        assert_eq!(client.send(request).await.unwrap().status, status);
+    }
+
+    // A fourth request matches no mock anymore
+    let request = client.get("http://example.com").build();
+    assert!(client.send(request).await.is_err());
+    assert_eq!(client.verify().unwrap_err().failures().len(), 1);
+}
+
+#[tokio::test]
+async fn test_error_response_with_retries() {
+    let client = MockClient::new();
+    client.mock(
+        Mock::new()
+            .respond_with(|_| Err(HttpError::Timeout(TimeoutKind::Connect)))
+            .times(2),
+    );
+    client.mock(Mock::new().respond(MockResponse::new(200)).times(1));
+
+    // The mock client can be moved into the code under test and verified through a clone
+    let retrying = RetryClient::new(client.clone(), NoopTimer).with_policy(RetryPolicy::new());
+    let request = retrying.get("http://example.com").build();
+    assert_eq!(retrying.send(request).await.unwrap().status, 200);
+
+    client.verify().unwrap();
+    assert_eq!(client.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_status_code() {
+    let client = MockClient::new();
+    client.mock(
+        Mock::new()
+            .url("http://example.com/moved")
+            .respond(MockResponse::new(302)),
+    );
+    client.mock(
+        Mock::new()
+            .url("http://example.com/custom")
+            .respond(MockResponse::new(299)),
+    );
+
+    let request = client.get("http://example.com/moved").build();
+    let response = client.send(request).await.unwrap();
+    assert_eq!(response.status, StatusCode::FOUND);
+    assert!(response.is_redirection());
+    assert!(!response.is_success() && !response.is_informational());
+    assert_eq!(response.status.to_string(), "302 Found");
+
+    // Unregistered status codes are kept as is.
+    let request = client.get("http://example.com/custom").build();
+    let response = client.send(request).await.unwrap();
+    assert_eq!(response.status, 299);
+    assert!(response.is_success());
+    assert_eq!(response.status.canonical_reason(), None);
+    assert_eq!(response.status.to_string(), "299");
+    assert_eq!(u16::from(response.status), 299);
+}
+
+#[tokio::test]
+async fn test_error_for_status() {
+    let client = MockClient::new();
+    client.mock(
+        Mock::new()
+            .url("http://example.com/ok")
+            .respond(MockResponse::new(204)),
+    );
+    client.mock(
+        Mock::new().url("http://example.com/missing").respond(
+            MockResponse::new(404)
+                .header("Content-Type", "text/plain")
+                .body("x".repeat(StatusError::BODY_LIMIT + 100)),
+        ),
+    );
+
+    let request = client.get("http://example.com/ok").build();
+    let response = client.send(request).await.unwrap();
+    let response = response.error_for_status::<MockError>().await.unwrap();
+    assert_eq!(response.status, 204);
+
+    let request = client.get("http://example.com/missing").build();
+    let response = client.send(request).await.unwrap();
+    match response.error_for_status::<MockError>().await {
+        Err(HttpError::Status(e)) => {
+            assert_eq!(e.status(), 404);
+            assert_eq!(e.url(), "http://example.com/missing");
+            assert_eq!(e.headers().content_type(), Some("text/plain"));
+            assert_eq!(e.body().len(), StatusError::BODY_LIMIT);
+        }
+        other => panic!("Expected status error, got: {:?}", other.map(|r| r.status)),
+    }
+
+    // With the builder option, `send` fails on its own.
+    let request = client
+        .get("http://example.com/missing")
+        .error_for_status(true)
+        .build();
+    let err = client.send(request).await.unwrap_err();
+    assert!(err.is_status());
+    assert_eq!(err.status(), Some(StatusCode::NOT_FOUND));
+    assert_eq!(
+        err.to_string(),
+        "HTTP client error status 404 Not Found for http://example.com/missing"
+    );
+}
+
+#[tokio::test]
+async fn test_error_for_status_with_retries() {
+    let client = MockClient::new();
+    client.mock(Mock::new().respond(MockResponse::new(503)).times(2));
+    client.mock(Mock::new().respond(MockResponse::new(200)).times(1));
+
+    let retrying = RetryClient::new(client.clone(), NoopTimer).with_policy(RetryPolicy::new());
+    let request = retrying
+        .get("http://example.com")
+        .error_for_status(true)
+        .build();
+    assert_eq!(retrying.send(request).await.unwrap().status, 200);
+
+    client.verify().unwrap();
+}
+
+#[tokio::test]
+async fn test_cancellation() {
+    let client = MockClient::new();
+    client.mock(Mock::new().respond(MockResponse::new(200)));
+
+    let parent = CancellationToken::new();
+    let child = parent.child();
+    let request = client.get("http://example.com").cancellation(child.clone());
+    assert_eq!(
+        client.send(request.clone().build()).await.unwrap().status,
+        200
+    );
+
+    // Cancelling the parent cancels the requests made with its children
+    parent.cancel();
+    assert!(child.is_cancelled());
+    let err = client.send(request.build()).await.unwrap_err();
+    assert!(matches!(err, HttpError::Cancelled));
+    assert!(err.is_cancelled());
+    assert_eq!(err.to_string(), "request cancelled");
+
+    // Children created after cancellation start cancelled, and cancelling a child does not
+    // cancel its parent
+    assert!(parent.child().is_cancelled());
+    let other = CancellationToken::new();
+    other.child().cancel();
+    assert!(!other.is_cancelled());
+
+    assert_eq!(client.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_deadline() {
+    let client = MockClient::new();
+    client.mock(Mock::new().respond(MockResponse::new(503)));
+
+    // Children inherit the earliest deadline of their ancestors
+    let now = Instant::now();
+    let parent = CancellationToken::with_deadline(now + Duration::from_secs(1));
+    assert_eq!(parent.child().deadline(), parent.deadline());
+    let child = parent.child_with_deadline(now + Duration::from_secs(60));
+    assert_eq!(child.deadline(), Some(now + Duration::from_secs(1)));
+    let child = parent.child_with_deadline(now + Duration::from_millis(10));
+    assert_eq!(child.deadline(), Some(now + Duration::from_millis(10)));
+
+    // Retrying is pointless if the deadline is reached before the next attempt
+    let retrying = RetryClient::new(client.clone(), NoopTimer).with_policy(
+        RetryPolicy::new()
+            .jitter(false)
+            .backoff(Duration::from_secs(5), Duration::from_secs(5)),
+    );
+    let request = retrying
+        .get("http://example.com")
+        .cancellation(parent.child())
+        .build();
+    assert_eq!(retrying.send(request).await.unwrap().status, 503);
+    assert_eq!(client.requests().len(), 1);
+
+    let expired = CancellationToken::with_deadline(Instant::now());
+    let request = client
+        .get("http://example.com")
+        .cancellation(expired)
+        .build();
+    let err = client.send(request).await.unwrap_err();
+    assert!(matches!(err, HttpError::Timeout(TimeoutKind::Request)));
+    assert!(!err.is_cancelled());
+}
+
+/// Timer cancelling a token while waiting, to test cancellations between retries.
+struct CancellingTimer(CancellationToken);
+
+impl Timer for CancellingTimer {
+    async fn sleep(&self, _duration: Duration) {
+        self.0.cancel();
+    }
+}
+
+#[tokio::test]
+async fn test_cancellation_with_retries() {
+    let client = MockClient::new();
+    client.mock(Mock::new().respond(MockResponse::new(503)));
+
+    let token = CancellationToken::new();
+    let retrying = RetryClient::new(client.clone(), CancellingTimer(token.clone()))
+        .with_policy(RetryPolicy::new());
+    let request = retrying
+        .get("http://example.com")
+        .cancellation(token)
+        .build();
+    let err = retrying.send(request).await.unwrap_err();
+    assert!(err.is_cancelled());
+    assert_eq!(client.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_chunked_body() {
+    let client = MockClient::new();
+    client.mock(
+        Mock::new().respond(MockResponse::new(200).chunks(["first".into(), "second".into()])),
+    );
+
+    let request = client.get("http://example.com").build();
+    let mut body = client.send(request).await.unwrap().body;
+    assert_eq!(body.next_chunk().await.unwrap().unwrap(), "first");
+    assert_eq!(body.next_chunk().await.unwrap().unwrap(), "second");
+    assert_eq!(body.next_chunk().await.unwrap(), None);
+}
+
+#[cfg(feature = "json")]
+mod json_tests {
+    use super::*;
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+    struct User {
+        name: String,
+        age: u32,
+    }
+
+    #[tokio::test]
+    async fn test_json_matcher_and_response() {
+        let user = User {
+            name: "Alice".to_string(),
+            age: 30,
+        };
+
+        let client = MockClient::new();
+        client.mock(
+            Mock::new()
+                .json(&serde_json::json!({ "age": 30, "name": "Alice" }))
+                .unwrap()
+                .respond(MockResponse::new(200).json(&user).unwrap()),
+        );
+
+        let request = client
+            .post("http://example.com")
+            .json(&user)
+            .unwrap()
+            .build();
+        let response = client.send(request).await.unwrap();
+        assert_eq!(response.headers.content_type(), Some("application/json"));
+        assert_eq!(response.body.json::<User>().await.unwrap(), user);
+        client.verify().unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_form_body_matcher() {
+    let client = MockClient::new();
+    client.mock(
+        Mock::new()
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body("name=John+Doe&note=a%26b%3Dc")
+            .respond(MockResponse::new(200)),
+    );
+
+    let request = client
+        .post("http://example.com")
+        .form([("name", "John Doe"), ("note", "a&b=c")])
+        .build();
+    assert_eq!(client.send(request).await.unwrap().status, 200);
+    client.verify().unwrap();
+}
+
+#[tokio::test]
+async fn test_defaults_middleware() {
+    let mock = MockClient::new();
+    mock.mock(Mock::new().respond(MockResponse::new(200)).at_least(4));
+    let client = mock.clone().with_middleware(
+        Defaults::new()
+            .base_url("http://example.com/api/v1")
+            .header("User-Agent", "tests")
+            .header("Accept", "application/json")
+            .query("key", "default"),
+    );
+
+    client.send(client.get("users").build()).await.unwrap();
+    client.send(client.get("/users/42").build()).await.unwrap();
+    let request = client
+        .get("")
+        .query("key", "mine")
+        .header("accept", "text/plain")
+        .build();
+    client.send(request).await.unwrap();
+    client
+        .send(client.get("https://other.com/status").build())
+        .await
+        .unwrap();
+
+    let requests = mock.requests();
+    assert_eq!(requests[0].url, "http://example.com/api/v1/users");
+    assert_eq!(requests[1].url, "http://example.com/api/v1/users/42");
+    assert_eq!(requests[2].url, "http://example.com/api/v1");
+    assert_eq!(requests[3].url, "https://other.com/status");
+
+    assert_eq!(requests[0].headers.get("user-agent").unwrap(), "tests");
+    assert_eq!(
+        requests[0].query,
+        [("key".to_string(), "default".to_string())]
+    );
+    assert_eq!(requests[2].headers.get_all("accept"), ["text/plain"]);
+    assert_eq!(requests[2].query, [("key".to_string(), "mine".to_string())]);
+    mock.verify().unwrap();
+}
+
+/// Token provider handing out `token-1`, `token-2`, etc.
+#[derive(Default)]
+struct CountingProvider {
+    fetched: AtomicUsize,
+}
+
+impl TokenProvider for CountingProvider {
+    type Error = std::io::Error;
+
+    async fn token(&self) -> Result<Token, Self::Error> {
+        let count = self.fetched.fetch_add(1, Ordering::SeqCst) + 1;
+        Ok(Token::new(format!("token-{count}")).expires_in(Duration::from_secs(3600)))
+    }
+}
+
+#[tokio::test]
+async fn test_token_auth_caches_and_refreshes() {
+    let mock = MockClient::new();
+    mock.mock(
+        Mock::new()
+            .header("authorization", "Bearer token-1")
+            .respond(MockResponse::new(200))
+            .times(2),
+    );
+    mock.mock(
+        Mock::new()
+            .header("authorization", "Bearer token-1")
+            .respond(MockResponse::new(401))
+            .times(1),
+    );
+    mock.mock(
+        Mock::new()
+            .header("authorization", "Bearer token-2")
+            .respond(MockResponse::new(200))
+            .times(1),
+    );
+    let client = mock
+        .clone()
+        .with_middleware(TokenAuth::new(CountingProvider::default()));
+
+    for _ in 0..3 {
+        let request = client.get("http://example.com/me").build();
+        assert_eq!(client.send(request).await.unwrap().status, 200);
+    }
+
+    assert_eq!(
+        client
+            .middleware()
+            .provider()
+            .fetched
+            .load(Ordering::SeqCst),
+        2
+    );
+    assert_eq!(mock.requests().len(), 4);
+    mock.verify().unwrap();
+}
+
+#[tokio::test]
+async fn test_token_auth_errors() {
+    struct FailingProvider;
+
+    impl TokenProvider for FailingProvider {
+        type Error = std::io::Error;
+
+        async fn token(&self) -> Result<Token, Self::Error> {
+            Err(std::io::Error::other("authorization server unavailable"))
+        }
+    }
+
+    let mock = MockClient::new();
+    mock.mock(Mock::new().respond(MockResponse::new(200)).times(1));
+    let client = mock
+        .clone()
+        .with_middleware(TokenAuth::new(FailingProvider));
+
+    let request = client.get("http://example.com/me").build();
+    match client.send(request).await {
+        Err(HttpError::Auth(e)) => assert_eq!(e.to_string(), "authorization server unavailable"),
+        other => panic!("Expected auth error, got: {:?}", other.map(|r| r.status)),
+    }
+
+    // Requests with their own credentials do not need the provider.
+    let request = client
+        .get("http://example.com/me")
+        .basic_auth("user", "pass")
+        .build();
+    assert_eq!(
+        request.headers.get("authorization").unwrap(),
+        "Basic dXNlcjpwYXNz"
+    );
+    assert_eq!(client.send(request).await.unwrap().status, 200);
+    mock.verify().unwrap();
+}
+
+#[cfg(feature = "form")]
+mod form_tests {
+    use super::*;
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+    struct Token {
+        access_token: String,
+        expires_in: u32,
+    }
+
+    #[tokio::test]
+    async fn test_form_serde_and_response() {
+        let client = MockClient::new();
+        client.mock(
+            Mock::new()
+                .body("grant_type=client_credentials&client_id=app")
+                .respond(MockResponse::new(200).body("access_token=abc&expires_in=3600")),
+        );
+
+        let request = client
+            .post("http://example.com/token")
+            .form_serde(&[("grant_type", "client_credentials"), ("client_id", "app")])
+            .unwrap()
+            .build();
+        let response = client.send(request).await.unwrap();
+        let token: Token = response.body.form().await.unwrap();
+        assert_eq!(
+            token,
+            Token {
+                access_token: "abc".to_string(),
+                expires_in: 3600,
+            }
+        );
+    }
+}
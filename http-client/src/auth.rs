@@ -0,0 +1,196 @@
+use core::fmt;
+use core::future::Future;
+use core::time::Duration;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Instant;
+
+use crate::body::BoxError;
+use crate::client::HttpClient;
+use crate::error::HttpError;
+use crate::middleware::{Middleware, Next};
+use crate::request::HttpRequest;
+use crate::response::HttpResponse;
+use crate::status::StatusCode;
+
+/// An access token, with its expiry if known.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Token {
+    value: String,
+    expires_at: Option<Instant>,
+}
+
+impl Token {
+    /// Creates a token that never expires.
+    pub fn new(value: impl Into<String>) -> Self {
+        Self {
+            value: value.into(),
+            expires_at: None,
+        }
+    }
+
+    /// Sets the lifetime of the token, from now (e.g. the `expires_in` of an OAuth 2.0 token
+    /// response).
+    pub fn expires_in(mut self, lifetime: Duration) -> Self {
+        self.expires_at = Some(Instant::now() + lifetime);
+        self
+    }
+
+    /// Returns the value of the token.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Returns the instant the token expires at, if known.
+    pub fn expires_at(&self) -> Option<Instant> {
+        self.expires_at
+    }
+}
+
+impl fmt::Debug for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The value is a secret, so keep it out of logs.
+        f.debug_struct("Token")
+            .field("value", &"[REDACTED]")
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+/// Trait for types fetching access tokens, used by [`TokenAuth`].
+///
+/// Implementations typically request a token from an authorization server (e.g. with the OAuth 2.0
+/// client credentials flow), possibly with an [`HttpClient`] of their own.
+pub trait TokenProvider: Send + Sync {
+    /// The error type returned when a token cannot be fetched.
+    type Error: Into<BoxError>;
+
+    /// Fetches a new token.
+    fn token(&self) -> impl Future<Output = Result<Token, Self::Error>> + Send;
+}
+
+/// A [`Middleware`] authenticating requests with bearer tokens fetched by a [`TokenProvider`].
+///
+/// Tokens are cached until shortly before they expire. If a request is rejected with a
+/// `401 Unauthorized` response, a new token is fetched and the request is sent again, once. Requests
+/// with a streaming body cannot be sent again, so their 401 responses are returned as is.
+///
+/// Requests with their own `Authorization` header are sent as is. Errors of the provider are
+/// returned as [`HttpError::Auth`].
+pub struct TokenAuth<P> {
+    provider: P,
+    cached: Mutex<Option<Token>>,
+    refresh_margin: Duration,
+}
+
+impl<P: TokenProvider> TokenAuth<P> {
+    /// Creates a middleware using tokens from the given provider.
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            cached: Mutex::new(None),
+            refresh_margin: Duration::from_secs(30),
+        }
+    }
+
+    /// Sets how long before their expiry tokens are refreshed, so that they do not expire while
+    /// a request is in flight.
+    ///
+    /// Defaults to 30 seconds.
+    pub fn refresh_margin(mut self, margin: Duration) -> Self {
+        self.refresh_margin = margin;
+        self
+    }
+
+    /// Returns a reference to the token provider.
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    /// Returns the cached token if it is still valid, or fetches a new one.
+    async fn token<E>(&self) -> Result<Token, HttpError<E>> {
+        let cached = self.lock().clone().filter(|token| {
+            token
+                .expires_at
+                .is_none_or(|expires_at| Instant::now() + self.refresh_margin < expires_at)
+        });
+        if let Some(token) = cached {
+            return Ok(token);
+        }
+
+        let token = self
+            .provider
+            .token()
+            .await
+            .map_err(|e| HttpError::Auth(e.into()))?;
+        *self.lock() = Some(token.clone());
+        Ok(token)
+    }
+
+    /// Forgets the given token if it is still cached, as it was rejected by the server.
+    fn invalidate(&self, token: &Token) {
+        let mut cached = self.lock();
+        if cached.as_ref() == Some(token) {
+            *cached = None;
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<Token>> {
+        self.cached
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<P: fmt::Debug> fmt::Debug for TokenAuth<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenAuth")
+            .field("provider", &self.provider)
+            .field("refresh_margin", &self.refresh_margin)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C, P> Middleware<C> for TokenAuth<P>
+where
+    C: HttpClient + Sync,
+    P: TokenProvider,
+{
+    async fn handle(
+        &self,
+        request: HttpRequest,
+        next: Next<'_, C>,
+    ) -> Result<HttpResponse<C::Body>, HttpError<C::Error>> {
+        if request.headers.contains("authorization") {
+            return next.run(request).await;
+        }
+
+        let token = self.token().await?;
+        let retry = request
+            .body
+            .as_ref()
+            .is_none_or(|body| body.is_replayable())
+            .then(|| request.clone());
+        let request = {
+            let result = next.run(authorize(request, &token)).await;
+            let unauthorized = match &result {
+                Ok(response) => response.status == StatusCode::UNAUTHORIZED,
+                Err(err) => err.status() == Some(StatusCode::UNAUTHORIZED),
+            };
+            match retry {
+                Some(request) if unauthorized => request,
+                _ => return result,
+            }
+        };
+
+        self.invalidate(&token);
+        let token = self.token().await?;
+        next.run(authorize(request, &token)).await
+    }
+}
+
+fn authorize(mut request: HttpRequest, token: &Token) -> HttpRequest {
+    request
+        .headers
+        .insert("Authorization", format!("Bearer {}", token.value));
+    request
+}
@@ -0,0 +1,255 @@
+use core::fmt;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use futures_core::Stream;
+
+/// A type-erased error, used by streaming request bodies.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// The body of an HTTP request.
+///
+/// A body is either a buffer of bytes, which can be sent any number of times, or a stream of
+/// chunks, which can only be sent once. Streaming bodies may have a known length, in which case
+/// client implementations should send it as `Content-Length` instead of using chunked
+/// transfer-encoding.
+///
+/// Cloning a streaming body does not duplicate the stream: all clones share it, and only the first
+/// one to be sent will be able to consume it. Use [`RequestBody::is_replayable`] to know whether a
+/// body can be sent more than once.
+#[derive(Clone)]
+pub struct RequestBody {
+    kind: Kind,
+}
+
+#[derive(Clone)]
+enum Kind {
+    Bytes(Bytes),
+    Stream {
+        stream: Arc<Mutex<Option<BodyStream>>>,
+        length: Option<u64>,
+    },
+}
+
+impl RequestBody {
+    /// Creates an empty body.
+    pub fn empty() -> Self {
+        Self::from(Bytes::new())
+    }
+
+    /// Creates a streaming body from a stream of chunks.
+    ///
+    /// If `length` is provided, it must match the total number of bytes produced by the stream.
+    pub fn from_stream<S, E>(stream: S, length: Option<u64>) -> Self
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+        E: Into<BoxError>,
+    {
+        Self {
+            kind: Kind::Stream {
+                stream: Arc::new(Mutex::new(Some(BodyStream::new(stream)))),
+                length,
+            },
+        }
+    }
+
+    /// Returns the length of the body in bytes, if known.
+    pub fn length(&self) -> Option<u64> {
+        match &self.kind {
+            Kind::Bytes(bytes) => Some(bytes.len() as u64),
+            Kind::Stream { length, .. } => *length,
+        }
+    }
+
+    /// Returns the body as a byte slice if it is buffered, or `None` if it is a stream.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match &self.kind {
+            Kind::Bytes(bytes) => Some(bytes),
+            Kind::Stream { .. } => None,
+        }
+    }
+
+    /// Returns true if the body can be sent more than once (i.e. it is buffered).
+    pub fn is_replayable(&self) -> bool {
+        matches!(self.kind, Kind::Bytes(_))
+    }
+
+    /// Consumes the body and returns its contents, to be sent by a client implementation.
+    ///
+    /// Fails if this is a streaming body that was already consumed through one of its clones.
+    pub fn into_content(self) -> Result<BodyContent, StreamConsumed> {
+        match self.kind {
+            Kind::Bytes(bytes) => Ok(BodyContent::Bytes(bytes)),
+            Kind::Stream { stream, length } => {
+                let stream = stream
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .take()
+                    .ok_or(StreamConsumed)?;
+                Ok(BodyContent::Stream(stream, length))
+            }
+        }
+    }
+}
+
+impl Default for RequestBody {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl fmt::Debug for RequestBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            Kind::Bytes(bytes) => f.debug_tuple("RequestBody::Bytes").field(bytes).finish(),
+            Kind::Stream { length, .. } => f
+                .debug_struct("RequestBody::Stream")
+                .field("length", length)
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+impl From<Bytes> for RequestBody {
+    fn from(bytes: Bytes) -> Self {
+        Self {
+            kind: Kind::Bytes(bytes),
+        }
+    }
+}
+
+impl From<Vec<u8>> for RequestBody {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::from(Bytes::from(bytes))
+    }
+}
+
+impl From<&[u8]> for RequestBody {
+    fn from(bytes: &[u8]) -> Self {
+        Self::from(Bytes::copy_from_slice(bytes))
+    }
+}
+
+impl<const N: usize> From<&[u8; N]> for RequestBody {
+    fn from(bytes: &[u8; N]) -> Self {
+        Self::from(Bytes::copy_from_slice(bytes))
+    }
+}
+
+impl From<String> for RequestBody {
+    fn from(text: String) -> Self {
+        Self::from(Bytes::from(text))
+    }
+}
+
+impl From<&str> for RequestBody {
+    fn from(text: &str) -> Self {
+        Self::from(Bytes::copy_from_slice(text.as_bytes()))
+    }
+}
+
+/// The contents of a [`RequestBody`], as consumed by client implementations.
+pub enum BodyContent {
+    /// A buffered body.
+    Bytes(Bytes),
+    /// A streaming body, along with its length if known.
+    Stream(BodyStream, Option<u64>),
+}
+
+/// A type-erased stream of body chunks.
+pub struct BodyStream {
+    inner: Pin<Box<dyn Stream<Item = Result<Bytes, BoxError>> + Send>>,
+}
+
+impl BodyStream {
+    fn new<S, E>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+        E: Into<BoxError>,
+    {
+        Self {
+            inner: Box::pin(MapErr { inner: stream }),
+        }
+    }
+}
+
+impl Stream for BodyStream {
+    type Item = Result<Bytes, BoxError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl fmt::Debug for BodyStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodyStream").finish_non_exhaustive()
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Converts the error type of a chunk stream into a [`BoxError`].
+    struct MapErr<S> {
+        #[pin]
+        inner: S,
+    }
+}
+
+impl<S, E> Stream for MapErr<S>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    E: Into<BoxError>,
+{
+    type Item = Result<Bytes, BoxError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.project()
+            .inner
+            .poll_next(cx)
+            .map(|item| item.map(|chunk| chunk.map_err(Into::into)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Error returned when a streaming body is sent after having already been consumed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamConsumed;
+
+impl fmt::Display for StreamConsumed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("streaming request body was already consumed")
+    }
+}
+
+impl std::error::Error for StreamConsumed {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bodies_from_borrowed_data() {
+        let payload = String::from("payload");
+
+        let body = RequestBody::from(payload.as_str());
+        assert_eq!(body.as_bytes(), Some(&b"payload"[..]));
+        assert_eq!(body.length(), Some(7));
+
+        let body = RequestBody::from(payload.as_bytes());
+        assert_eq!(body.as_bytes(), Some(&b"payload"[..]));
+
+        let array = [1, 2, 3];
+        let body = RequestBody::from(&array);
+        assert_eq!(body.as_bytes(), Some(&[1, 2, 3][..]));
+        assert!(body.is_replayable());
+    }
+}
@@ -0,0 +1,215 @@
+use core::fmt;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Instant;
+
+/// A token used to cancel requests, optionally carrying a deadline.
+///
+/// Attach a token to a request with
+/// [`HttpRequestBuilder::cancellation`](crate::HttpRequestBuilder::cancellation). Once the token
+/// is cancelled, sending the request fails with [`HttpError::Cancelled`](crate::HttpError::Cancelled),
+/// including while the response body is being read. When the deadline of the token is reached,
+/// the request fails as if its request timeout elapsed.
+///
+/// Tokens form a tree: a [child token](Self::child) is cancelled when its parent is, and
+/// inherits its deadline, so that nested calls made on behalf of a request never outlive it.
+/// Cloned tokens share the same state.
+///
+/// ```rust
+/// use std::time::{Duration, Instant};
+///
+/// use http_client::CancellationToken;
+///
+/// let parent = CancellationToken::with_deadline(Instant::now() + Duration::from_secs(5));
+/// let child = parent.child_with_deadline(Instant::now() + Duration::from_secs(10));
+/// assert_eq!(child.deadline(), parent.deadline());
+///
+/// parent.cancel();
+/// assert!(child.is_cancelled());
+/// ```
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    deadline: Option<Instant>,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    cancelled: bool,
+    // The wakers of the pending `Cancelled` futures, by id.
+    wakers: Vec<(u64, Waker)>,
+    next_waker_id: u64,
+    children: Vec<Weak<Inner>>,
+}
+
+impl CancellationToken {
+    /// Creates a new token, without deadline.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new token with the given deadline.
+    pub fn with_deadline(deadline: Instant) -> Self {
+        Self::from_inner(Inner {
+            deadline: Some(deadline),
+            state: Mutex::default(),
+        })
+    }
+
+    /// Creates a child token, cancelled when this token is and inheriting its deadline.
+    ///
+    /// Cancelling the child does not cancel its parent.
+    pub fn child(&self) -> Self {
+        self.child_inner(self.inner.deadline)
+    }
+
+    /// Creates a child token with the given deadline, or the deadline of this token if it is
+    /// earlier.
+    pub fn child_with_deadline(&self, deadline: Instant) -> Self {
+        let deadline = match self.inner.deadline {
+            Some(parent) => parent.min(deadline),
+            None => deadline,
+        };
+        self.child_inner(Some(deadline))
+    }
+
+    /// Returns the deadline of the token, if any.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.inner.deadline
+    }
+
+    /// Cancels the token and all of its children, waking up the tasks waiting for it.
+    pub fn cancel(&self) {
+        cancel(&self.inner);
+    }
+
+    /// Returns true if the token was cancelled.
+    ///
+    /// Reaching the deadline does not cancel the token.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.lock().cancelled
+    }
+
+    /// Waits until the token is cancelled.
+    pub fn cancelled(&self) -> impl Future<Output = ()> + Send + '_ {
+        Cancelled {
+            inner: &self.inner,
+            id: None,
+        }
+    }
+
+    fn from_inner(inner: Inner) -> Self {
+        Self {
+            inner: Arc::new(inner),
+        }
+    }
+
+    fn child_inner(&self, deadline: Option<Instant>) -> Self {
+        let child = Self::from_inner(Inner {
+            deadline,
+            state: Mutex::default(),
+        });
+
+        let mut state = self.inner.lock();
+        if state.cancelled {
+            child.inner.lock().cancelled = true;
+        } else {
+            // Forget the children that were dropped, so that long-lived parents do not
+            // accumulate them.
+            state.children.retain(|child| child.strong_count() > 0);
+            state.children.push(Arc::downgrade(&child.inner));
+        }
+        child
+    }
+}
+
+impl Inner {
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn cancel(inner: &Inner) {
+    let (wakers, children) = {
+        let mut state = inner.lock();
+        if state.cancelled {
+            return;
+        }
+        state.cancelled = true;
+        (
+            std::mem::take(&mut state.wakers),
+            std::mem::take(&mut state.children),
+        )
+    };
+
+    for (_, waker) in wakers {
+        waker.wake();
+    }
+    for child in children.iter().filter_map(Weak::upgrade) {
+        cancel(&child);
+    }
+}
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .field("deadline", &self.inner.deadline)
+            .finish()
+    }
+}
+
+/// The future returned by [`CancellationToken::cancelled`].
+struct Cancelled<'a> {
+    inner: &'a Inner,
+    id: Option<u64>,
+}
+
+impl Future for Cancelled<'_> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let inner = self.inner;
+        let mut state = inner.lock();
+        if state.cancelled {
+            return Poll::Ready(());
+        }
+
+        let registered = self.id.and_then(|id| {
+            state
+                .wakers
+                .iter_mut()
+                .find(|(waker_id, _)| *waker_id == id)
+        });
+        match registered {
+            Some((_, waker)) => waker.clone_from(cx.waker()),
+            None => {
+                let id = state.next_waker_id;
+                state.next_waker_id += 1;
+                state.wakers.push((id, cx.waker().clone()));
+                self.id = Some(id);
+            }
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for Cancelled<'_> {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            self.inner
+                .lock()
+                .wakers
+                .retain(|(waker_id, _)| *waker_id != id);
+        }
+    }
+}
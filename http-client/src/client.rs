@@ -2,10 +2,14 @@ use core::future::Future;
 
 use crate::error::HttpError;
 use crate::method::HttpMethod;
+use crate::middleware::{Layer, Middleware, MiddlewareClient};
 use crate::request::{HttpRequest, HttpRequestBuilder};
 use crate::response::{HttpResponse, ResponseBody};
 
 /// Trait for HTTP clients that can send requests.
+///
+/// Request URLs must be absolute, unless a [`Defaults`](crate::Defaults) middleware resolves them
+/// against a base URL.
 pub trait HttpClient {
     /// The response body type returned by the underlying HTTP crate.
     type Body: ResponseBody;
@@ -38,6 +42,44 @@ pub trait HttpClient {
         HttpRequestBuilder::new(HttpMethod::Delete, url_path)
     }
 
+    /// Creates a HEAD request builder for the given URL path.
+    fn head(&self, url_path: impl Into<String>) -> HttpRequestBuilder {
+        HttpRequestBuilder::new(HttpMethod::Head, url_path)
+    }
+
+    /// Creates an OPTIONS request builder for the given URL path.
+    fn options(&self, url_path: impl Into<String>) -> HttpRequestBuilder {
+        HttpRequestBuilder::new(HttpMethod::Options, url_path)
+    }
+
+    /// Creates a request builder with an arbitrary method for the given URL path.
+    ///
+    /// This is mostly useful for methods without a dedicated helper, such as `TRACE` or extension
+    /// methods like WebDAV's `PROPFIND`.
+    fn request(&self, method: HttpMethod, url_path: impl Into<String>) -> HttpRequestBuilder {
+        HttpRequestBuilder::new(method, url_path)
+    }
+
+    /// Wraps this client with a middleware, which will handle every request sent through it.
+    ///
+    /// When stacking several middlewares, the last one added is the first to handle requests.
+    fn with_middleware<M>(self, middleware: M) -> MiddlewareClient<Self, M>
+    where
+        Self: Sized,
+        M: Middleware<Self>,
+    {
+        MiddlewareClient::new(self, middleware)
+    }
+
+    /// Wraps this client with the given layer.
+    fn with_layer<L>(self, layer: L) -> L::Client
+    where
+        Self: Sized,
+        L: Layer<Self>,
+    {
+        layer.layer(self)
+    }
+
     /// Sends an HTTP request and returns the response.
     fn send(
         &self,
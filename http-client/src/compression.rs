@@ -0,0 +1,46 @@
+use bytes::Bytes;
+
+/// A compression algorithm for request bodies, set with
+/// [`HttpRequestBuilder::compress`](crate::HttpRequestBuilder::compress).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// gzip, which all servers accepting compressed bodies should support.
+    #[cfg(feature = "gzip")]
+    Gzip,
+    /// Zstandard, which compresses better and faster than gzip, but is less widely supported.
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Compression {
+    /// Returns the `Content-Encoding` header value of the algorithm.
+    pub fn content_encoding(self) -> &'static str {
+        match self {
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => "gzip",
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    /// Compresses the given bytes.
+    pub(crate) fn compress(self, data: &[u8]) -> Bytes {
+        let compressed = match self {
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => {
+                use std::io::Write;
+
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder
+                    .write_all(data)
+                    .and_then(|()| encoder.finish())
+                    .expect("compressing in memory cannot fail")
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => zstd::encode_all(data, zstd::DEFAULT_COMPRESSION_LEVEL)
+                .expect("compressing in memory cannot fail"),
+        };
+        Bytes::from(compressed)
+    }
+}
@@ -0,0 +1,251 @@
+use core::fmt;
+use core::future::Future;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use cookie_store::{CookieStore, RawCookie};
+use url::Url;
+
+use crate::body::BoxError;
+use crate::client::HttpClient;
+use crate::error::HttpError;
+use crate::headers::HeaderMap;
+use crate::middleware::{Middleware, Next};
+use crate::request::HttpRequest;
+use crate::response::HttpResponse;
+
+/// A cookie store, capturing the cookies set by responses and sending them back with subsequent
+/// requests.
+///
+/// Cookies are matched against requests according to their domain, path, expiry and `Secure`
+/// attributes. When given a [`PublicSuffixList`] (see
+/// [`with_public_suffix_list`](Self::with_public_suffix_list)), cookies whose `Domain` attribute
+/// is a public suffix (e.g. `Domain=co.uk`) are rejected, so that a site cannot set cookies for
+/// every other site under the same suffix. Cloning a jar is cheap, and clones share the same
+/// cookies.
+///
+/// A jar is used as a [`Middleware`], which works with any client. Clients following redirects
+/// on their own may also support cookie jars directly (e.g. `HyperClientBuilder::cookie_jar`), so
+/// that cookies are handled on every redirect as well.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # async fn example(client: impl http_client::HttpClient + Sync) -> Result<(), Box<dyn std::error::Error>> {
+/// use http_client::{CookieJar, HttpClient};
+///
+/// let jar = CookieJar::load_file("cookies.json")?;
+/// let client = client.with_middleware(jar.clone());
+///
+/// // ... send requests ...
+///
+/// jar.save_file("cookies.json")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CookieJar {
+    store: Arc<Mutex<CookieStore>>,
+}
+
+/// A parsed [Public Suffix List](https://publicsuffix.org/), used by [`CookieJar`] to reject
+/// cookies set for a whole public suffix.
+///
+/// The list is parsed from its text format, e.g. a copy of
+/// `https://publicsuffix.org/list/public_suffix_list.dat` kept up to date by the application:
+///
+/// ```rust,no_run
+/// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use http_client::{CookieJar, PublicSuffixList};
+///
+/// let list: PublicSuffixList = std::fs::read_to_string("public_suffix_list.dat")?.parse()?;
+/// let jar = CookieJar::new().with_public_suffix_list(list);
+/// # Ok(())
+/// # }
+/// ```
+pub use publicsuffix::List as PublicSuffixList;
+
+impl CookieJar {
+    /// Creates an empty cookie jar.
+    pub fn new() -> Self {
+        Self::from_store(CookieStore::default())
+    }
+
+    /// Loads cookies previously saved with [`save_json`](Self::save_json), skipping expired ones.
+    pub fn load_json(reader: impl BufRead) -> Result<Self, CookieJarError> {
+        let store = cookie_store::serde::json::load(reader).map_err(CookieJarError::Format)?;
+        Ok(Self::from_store(store))
+    }
+
+    fn from_store(store: CookieStore) -> Self {
+        Self {
+            store: Arc::new(Mutex::new(store)),
+        }
+    }
+
+    /// Sets the public suffix list used to reject cookies set for a whole public suffix.
+    ///
+    /// Without a list, such cookies are accepted. The list is moved into the jar rather than
+    /// shared: parse it once, and clone the jar rather than the list where several parts of an
+    /// application need it, since copying a full list is costly.
+    pub fn with_public_suffix_list(self, list: PublicSuffixList) -> Self {
+        {
+            let mut store = self.lock();
+            let cookies = std::mem::take(&mut *store);
+            *store = cookies.with_suffix_list(list);
+        }
+        self
+    }
+
+    /// Loads cookies previously saved with [`save_file`](Self::save_file), or creates an empty jar
+    /// if the file does not exist.
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self, CookieJarError> {
+        match std::fs::File::open(path) {
+            Ok(file) => Self::load_json(io::BufReader::new(file)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Saves the cookies of the jar as JSON.
+    ///
+    /// Session cookies (without an expiry date) and expired cookies are not saved.
+    pub fn save_json(&self, writer: &mut impl Write) -> Result<(), CookieJarError> {
+        cookie_store::serde::json::save(&self.lock(), writer).map_err(CookieJarError::Format)
+    }
+
+    /// Saves the cookies of the jar as JSON to the given file, overwriting it.
+    ///
+    /// Session cookies (without an expiry date) and expired cookies are not saved.
+    pub fn save_file(&self, path: impl AsRef<Path>) -> Result<(), CookieJarError> {
+        let mut file = io::BufWriter::new(std::fs::File::create(path)?);
+        self.save_json(&mut file)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Adds a cookie, given as a `Set-Cookie` header value received from `url`.
+    pub fn add_cookie(&self, set_cookie: &str, url: &str) -> Result<(), CookieJarError> {
+        let url = Url::parse(url)?;
+        self.lock()
+            .parse(set_cookie, &url)
+            .map_err(|e| CookieJarError::InvalidCookie(e.into()))?;
+        Ok(())
+    }
+
+    /// Returns the value of the `Cookie` header to send to `url`, if any cookie matches it.
+    pub fn cookie_header(&self, url: &str) -> Option<String> {
+        let url = Url::parse(url).ok()?;
+        let store = self.lock();
+        let header = store
+            .get_request_values(&url)
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join("; ");
+        (!header.is_empty()).then_some(header)
+    }
+
+    /// Stores the cookies set by the `Set-Cookie` headers of a response received from `url`.
+    ///
+    /// Invalid cookies, and cookies that `url` is not allowed to set, are ignored.
+    pub fn store_response_cookies(&self, url: &str, headers: &HeaderMap) {
+        let Ok(url) = Url::parse(url) else {
+            return;
+        };
+        let cookies = headers
+            .get_all("set-cookie")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| RawCookie::parse(value.to_string()).ok());
+        self.lock().store_response_cookies(cookies, &url);
+    }
+
+    /// Removes all the cookies of the jar.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CookieStore> {
+        self.store
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for CookieJar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> Middleware<C> for CookieJar
+where
+    C: HttpClient + Sync,
+{
+    fn handle(
+        &self,
+        mut request: HttpRequest,
+        next: Next<'_, C>,
+    ) -> impl Future<Output = Result<HttpResponse<C::Body>, HttpError<C::Error>>> + Send {
+        // Cookie headers set explicitly on the request take precedence over the jar.
+        if !request.headers.contains("cookie")
+            && let Some(cookies) = self.cookie_header(&request.url)
+        {
+            request.headers.insert("Cookie", cookies);
+        }
+
+        async move {
+            let response = next.run(request).await?;
+            self.store_response_cookies(&response.url, &response.headers);
+            Ok(response)
+        }
+    }
+}
+
+/// Errors that can occur when using a [`CookieJar`].
+#[derive(Debug)]
+pub enum CookieJarError {
+    /// Error reading or writing a cookie file.
+    Io(io::Error),
+    /// Invalid saved cookies.
+    Format(BoxError),
+    /// The URL a cookie was added for is invalid.
+    InvalidUrl(url::ParseError),
+    /// The cookie is invalid, or cannot be set by the given URL.
+    InvalidCookie(BoxError),
+}
+
+impl fmt::Display for CookieJarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CookieJarError::Io(e) => write!(f, "cookie jar io error: {e}"),
+            CookieJarError::Format(e) => write!(f, "invalid cookie jar: {e}"),
+            CookieJarError::InvalidUrl(e) => write!(f, "invalid cookie URL: {e}"),
+            CookieJarError::InvalidCookie(e) => write!(f, "invalid cookie: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for CookieJarError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CookieJarError::Io(e) => Some(e),
+            CookieJarError::Format(e) => Some(e.as_ref()),
+            CookieJarError::InvalidUrl(e) => Some(e),
+            CookieJarError::InvalidCookie(e) => Some(e.as_ref()),
+        }
+    }
+}
+
+impl From<io::Error> for CookieJarError {
+    fn from(err: io::Error) -> Self {
+        CookieJarError::Io(err)
+    }
+}
+
+impl From<url::ParseError> for CookieJarError {
+    fn from(err: url::ParseError) -> Self {
+        CookieJarError::InvalidUrl(err)
+    }
+}
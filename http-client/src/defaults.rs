@@ -0,0 +1,123 @@
+use core::future::Future;
+
+use crate::client::HttpClient;
+use crate::error::HttpError;
+use crate::headers::{HeaderMap, HeaderValue};
+use crate::middleware::{Middleware, Next};
+use crate::request::{HttpRequest, push_path};
+use crate::response::HttpResponse;
+
+/// A [`Middleware`] applying a base URL, default headers and default query parameters to every
+/// request.
+///
+/// Requests with a relative URL (e.g. `users/42` or `/users/42`) have it appended to the base URL,
+/// like [`HttpRequestBuilder::path`](crate::HttpRequestBuilder::path) does, while absolute URLs
+/// are left as is. Default headers and query parameters are only added to requests that do not
+/// set them already.
+///
+/// # Example
+///
+/// ```rust
+/// use http_client::{Defaults, HttpClient};
+///
+/// # fn example(client: impl HttpClient + Sync) {
+/// let client = client.with_middleware(
+///     Defaults::new()
+///         .base_url("https://api.example.com/v1")
+///         .header("User-Agent", "my-app/1.0")
+///         .header("Accept", "application/json")
+///         .query("api_key", "secret"),
+/// );
+///
+/// // Sent to `https://api.example.com/v1/users/42?api_key=secret`.
+/// let request = client.get("/users/42").build();
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Defaults {
+    base_url: Option<String>,
+    headers: HeaderMap,
+    query: Vec<(String, String)>,
+}
+
+impl Defaults {
+    /// Creates a middleware without any default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the base URL that relative request URLs are appended to.
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = Some(url.into());
+        self
+    }
+
+    /// Adds a default header.
+    ///
+    /// Several values can be added for the same header, in which case they are all added to
+    /// requests without this header.
+    pub fn header(mut self, key: impl Into<String>, value: impl Into<HeaderValue>) -> Self {
+        self.headers.append(key, value);
+        self
+    }
+
+    /// Adds a default query parameter.
+    pub fn query(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query.push((key.into(), value.into()));
+        self
+    }
+
+    /// Applies the defaults to a request.
+    pub fn apply(&self, mut request: HttpRequest) -> HttpRequest {
+        if let Some(base_url) = &self.base_url
+            && !is_absolute(&request.url)
+        {
+            let mut url = base_url.clone();
+            if !request.url.is_empty() {
+                push_path(&mut url, &request.url);
+            }
+            request.url = url;
+        }
+
+        let headers = self
+            .headers
+            .iter()
+            .filter(|(name, _)| !request.headers.contains(name))
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect::<Vec<_>>();
+        request.headers.extend(headers);
+
+        let query = self
+            .query
+            .iter()
+            .filter(|(key, _)| !request.query.iter().any(|(k, _)| k == key))
+            .cloned()
+            .collect::<Vec<_>>();
+        request.query.extend(query);
+
+        request
+    }
+}
+
+impl<C> Middleware<C> for Defaults
+where
+    C: HttpClient + Sync,
+{
+    fn handle(
+        &self,
+        request: HttpRequest,
+        next: Next<'_, C>,
+    ) -> impl Future<Output = Result<HttpResponse<C::Body>, HttpError<C::Error>>> + Send {
+        next.run(self.apply(request))
+    }
+}
+
+/// Returns true if the URL starts with a scheme (e.g. `https://`).
+fn is_absolute(url: &str) -> bool {
+    url.split_once("://").is_some_and(|(scheme, _)| {
+        scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
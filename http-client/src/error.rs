@@ -1,11 +1,39 @@
 use core::fmt;
 
+use bytes::Bytes;
+
+use crate::body::BoxError;
+use crate::headers::HeaderMap;
+use crate::status::StatusCode;
+use crate::timeout::TimeoutKind;
+
 /// Errors that can occur when building and sending HTTP requests.
 #[derive(Debug)]
 pub enum HttpError<E> {
     /// The URL provided was invalid.
     InvalidUrl(String),
 
+    /// The request did not complete before one of its timeouts elapsed.
+    ///
+    /// Timeouts elapsing while reading the response body are reported by the body error instead
+    /// (see [`Timeouts`](crate::Timeouts)).
+    Timeout(TimeoutKind),
+
+    /// A redirect could not be followed, e.g. because there were too many of them.
+    Redirect(String),
+
+    /// The credentials of the request could not be obtained, e.g. because a
+    /// [`TokenProvider`](crate::TokenProvider) failed.
+    Auth(BoxError),
+
+    /// The server responded with a 4xx or 5xx status, see
+    /// [`HttpResponse::error_for_status`](crate::HttpResponse::error_for_status).
+    Status(Box<StatusError>),
+
+    /// The request was cancelled through its
+    /// [`CancellationToken`](crate::CancellationToken).
+    Cancelled,
+
     /// An error occurred during JSON serialization.
     #[cfg(feature = "json")]
     Serialization(serde_json::Error),
@@ -14,10 +42,106 @@ pub enum HttpError<E> {
     Client(E),
 }
 
+impl<E> HttpError<E> {
+    /// Converts the client error, leaving other errors as is.
+    ///
+    /// This is mostly useful for client wrappers exposing a different error type than the client
+    /// they wrap.
+    pub fn map_client<F>(self, f: impl FnOnce(E) -> F) -> HttpError<F> {
+        match self {
+            HttpError::InvalidUrl(url) => HttpError::InvalidUrl(url),
+            HttpError::Timeout(kind) => HttpError::Timeout(kind),
+            HttpError::Redirect(reason) => HttpError::Redirect(reason),
+            HttpError::Auth(err) => HttpError::Auth(err),
+            HttpError::Status(err) => HttpError::Status(err),
+            HttpError::Cancelled => HttpError::Cancelled,
+            #[cfg(feature = "json")]
+            HttpError::Serialization(err) => HttpError::Serialization(err),
+            HttpError::Client(err) => HttpError::Client(f(err)),
+        }
+    }
+}
+
+impl<E> HttpError<E> {
+    /// Returns true if the server responded with an error status.
+    pub fn is_status(&self) -> bool {
+        matches!(self, HttpError::Status(_))
+    }
+
+    /// Returns the status of the response, if the server responded with an error status.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            HttpError::Status(err) => Some(err.status),
+            _ => None,
+        }
+    }
+}
+
+impl<E: ClassifyError> HttpError<E> {
+    /// Returns true if the error occurred while connecting to the server (e.g. the name could not
+    /// be resolved, the connection was refused or the connect timeout elapsed).
+    pub fn is_connect(&self) -> bool {
+        match self {
+            HttpError::Timeout(kind) => *kind == TimeoutKind::Connect,
+            HttpError::Client(err) => err.is_connect(),
+            _ => false,
+        }
+    }
+
+    /// Returns true if one of the timeouts of the request elapsed.
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            HttpError::Timeout(_) => true,
+            HttpError::Client(err) => err.is_timeout(),
+            _ => false,
+        }
+    }
+
+    /// Returns true if the error is related to TLS, e.g. a failed handshake or an invalid
+    /// certificate.
+    pub fn is_tls(&self) -> bool {
+        match self {
+            HttpError::Client(err) => err.is_tls(),
+            _ => false,
+        }
+    }
+
+    /// Returns true if the error occurred while reading or writing a body.
+    pub fn is_body(&self) -> bool {
+        match self {
+            HttpError::Client(err) => err.is_body(),
+            _ => false,
+        }
+    }
+
+    /// Returns true if a redirect could not be followed.
+    pub fn is_redirect(&self) -> bool {
+        match self {
+            HttpError::Redirect(_) => true,
+            HttpError::Client(err) => err.is_redirect(),
+            _ => false,
+        }
+    }
+
+    /// Returns true if the request was cancelled, including while reading the response body.
+    pub fn is_cancelled(&self) -> bool {
+        match self {
+            HttpError::Cancelled => true,
+            HttpError::Client(err) => err.is_cancelled(),
+            _ => false,
+        }
+    }
+}
+
 impl<E: fmt::Display> fmt::Display for HttpError<E> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             HttpError::InvalidUrl(url) => write!(f, "invalid URL: {url}"),
+            HttpError::Timeout(kind) => write!(f, "{kind} elapsed"),
+            HttpError::Redirect(reason) => write!(f, "redirect error: {reason}"),
+            HttpError::Auth(err) => write!(f, "authentication error: {err}"),
+            HttpError::Status(err) => write!(f, "{err}"),
+            HttpError::Cancelled => write!(f, "request cancelled"),
             #[cfg(feature = "json")]
             HttpError::Serialization(err) => write!(f, "JSON serialization error: {err}"),
             HttpError::Client(err) => write!(f, "client error: {err}"),
@@ -29,6 +153,12 @@ impl<E: std::error::Error + 'static> std::error::Error for HttpError<E> {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             HttpError::InvalidUrl(_) => None,
+            HttpError::Timeout(_) => None,
+            HttpError::Redirect(_) => None,
+            HttpError::Auth(err) => Some(err.as_ref()),
+            // The status error has no source, and is already displayed by this error.
+            HttpError::Status(_) => None,
+            HttpError::Cancelled => None,
             #[cfg(feature = "json")]
             HttpError::Serialization(err) => Some(err),
             HttpError::Client(err) => Some(err),
@@ -36,8 +166,108 @@ impl<E: std::error::Error + 'static> std::error::Error for HttpError<E> {
     }
 }
 
+/// A 4xx or 5xx response, turned into an error by
+/// [`HttpResponse::error_for_status`](crate::HttpResponse::error_for_status).
+///
+/// Only the first [`BODY_LIMIT`](Self::BODY_LIMIT) bytes of the response body are kept, which is
+/// usually enough to include the error details sent by the server.
+#[derive(Debug, Clone)]
+pub struct StatusError {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+    url: String,
+}
+
+impl StatusError {
+    /// The maximum number of bytes of the response body kept in the error.
+    pub const BODY_LIMIT: usize = 4096;
+
+    pub(crate) fn new(status: StatusCode, headers: HeaderMap, body: Bytes, url: String) -> Self {
+        Self {
+            status,
+            headers,
+            body,
+            url,
+        }
+    }
+
+    /// Returns the status code of the response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns the headers of the response.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns the start of the response body, up to [`BODY_LIMIT`](Self::BODY_LIMIT) bytes.
+    ///
+    /// The body is cut short if an error occurred while reading it.
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// Returns the URL of the response.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl fmt::Display for StatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.status.is_client_error() {
+            "client error"
+        } else {
+            "server error"
+        };
+        write!(f, "HTTP {kind} status {} for {}", self.status, self.url)
+    }
+}
+
+impl std::error::Error for StatusError {}
+
 impl<E> From<E> for HttpError<E> {
     fn from(value: E) -> Self {
         HttpError::Client(value)
     }
 }
+
+/// Trait for client errors that can be classified, backing the classification methods of
+/// [`HttpError`] (e.g. [`HttpError::is_connect`]).
+///
+/// Implementation crates implement it for their error types, so that callers can handle errors
+/// without knowing which client produced them. An error may fall into several categories (e.g. a
+/// failed TLS handshake is both a connect and a TLS error), or none of them.
+pub trait ClassifyError {
+    /// Returns true if the error occurred while connecting to the server.
+    fn is_connect(&self) -> bool {
+        false
+    }
+
+    /// Returns true if a timeout elapsed.
+    fn is_timeout(&self) -> bool {
+        false
+    }
+
+    /// Returns true if the error is related to TLS.
+    fn is_tls(&self) -> bool {
+        false
+    }
+
+    /// Returns true if the error occurred while reading or writing a body.
+    fn is_body(&self) -> bool {
+        false
+    }
+
+    /// Returns true if a redirect could not be followed.
+    fn is_redirect(&self) -> bool {
+        false
+    }
+
+    /// Returns true if the request was cancelled.
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
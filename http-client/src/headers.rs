@@ -0,0 +1,331 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use bytes::Bytes;
+
+/// A collection of HTTP headers.
+///
+/// Header names are case-insensitive: they are stored in lowercase, and lookups ignore case. A
+/// header may have several values, which are kept in the order they were added.
+#[derive(Clone, Default)]
+pub struct HeaderMap {
+    entries: Vec<(String, Vec<HeaderValue>)>,
+    index: HashMap<String, usize>,
+}
+
+impl HeaderMap {
+    /// Creates an empty header map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the first value of the given header, if any.
+    pub fn get(&self, name: &str) -> Option<&HeaderValue> {
+        self.get_all(name).first()
+    }
+
+    /// Returns all the values of the given header, in order.
+    pub fn get_all(&self, name: &str) -> &[HeaderValue] {
+        match self.position(name) {
+            Some(position) => &self.entries[position].1,
+            None => &[],
+        }
+    }
+
+    /// Returns true if the map contains the given header.
+    pub fn contains(&self, name: &str) -> bool {
+        self.position(name).is_some()
+    }
+
+    /// Sets the value of a header, replacing any previous values.
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<HeaderValue>) {
+        let name = lowercase(name.into());
+        match self.index.get(&name) {
+            Some(&position) => self.entries[position].1 = vec![value.into()],
+            None => self.push(name, value.into()),
+        }
+    }
+
+    /// Adds a value to a header, keeping any previous values.
+    pub fn append(&mut self, name: impl Into<String>, value: impl Into<HeaderValue>) {
+        let name = lowercase(name.into());
+        match self.index.get(&name) {
+            Some(&position) => self.entries[position].1.push(value.into()),
+            None => self.push(name, value.into()),
+        }
+    }
+
+    /// Removes a header, returning its values.
+    pub fn remove(&mut self, name: &str) -> Vec<HeaderValue> {
+        match self.position(name) {
+            Some(position) => {
+                let (_, values) = self.entries.remove(position);
+                self.reindex();
+                values
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Only keeps the headers for which the predicate returns true, given their (lowercase) name.
+    pub fn retain(&mut self, mut f: impl FnMut(&str) -> bool) {
+        self.entries.retain(|(name, _)| f(name));
+        self.reindex();
+    }
+
+    /// Returns an iterator over all the headers as (lowercase) name and value pairs.
+    ///
+    /// Headers with several values are yielded once per value.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &HeaderValue)> {
+        self.entries
+            .iter()
+            .flat_map(|(name, values)| values.iter().map(move |value| (name.as_str(), value)))
+    }
+
+    /// Returns the number of header values in the map.
+    pub fn len(&self) -> usize {
+        self.entries.iter().map(|(_, values)| values.len()).sum()
+    }
+
+    /// Returns true if the map contains no headers.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the value of the `Content-Type` header, if present and valid.
+    pub fn content_type(&self) -> Option<&str> {
+        self.get("content-type")?.to_str().ok()
+    }
+
+    /// Returns the value of the `Content-Length` header, if present and valid.
+    pub fn content_length(&self) -> Option<u64> {
+        self.get("content-length")?
+            .to_str()
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        if name.bytes().any(|b| b.is_ascii_uppercase()) {
+            self.index.get(&name.to_ascii_lowercase()).copied()
+        } else {
+            self.index.get(name).copied()
+        }
+    }
+
+    fn push(&mut self, name: String, value: HeaderValue) {
+        self.index.insert(name.clone(), self.entries.len());
+        self.entries.push((name, vec![value]));
+    }
+
+    fn reindex(&mut self) {
+        self.index.clear();
+        for (position, (name, _)) in self.entries.iter().enumerate() {
+            self.index.insert(name.clone(), position);
+        }
+    }
+}
+
+fn lowercase(mut name: String) -> String {
+    name.make_ascii_lowercase();
+    name
+}
+
+impl fmt::Debug for HeaderMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl PartialEq for HeaderMap {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries.len() == other.entries.len()
+            && self
+                .entries
+                .iter()
+                .all(|(name, values)| other.get_all(name) == values.as_slice())
+    }
+}
+
+impl Eq for HeaderMap {}
+
+impl<K, V> FromIterator<(K, V)> for HeaderMap
+where
+    K: Into<String>,
+    V: Into<HeaderValue>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut headers = Self::new();
+        headers.extend(iter);
+        headers
+    }
+}
+
+impl<K, V> Extend<(K, V)> for HeaderMap
+where
+    K: Into<String>,
+    V: Into<HeaderValue>,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (name, value) in iter {
+            self.append(name, value);
+        }
+    }
+}
+
+/// The value of an HTTP header.
+///
+/// Header values are usually text, but may hold arbitrary bytes. Use [`HeaderValue::to_str`] to
+/// get a value as text.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct HeaderValue {
+    bytes: Bytes,
+}
+
+impl HeaderValue {
+    /// Creates a header value from a static string.
+    pub fn from_static(value: &'static str) -> Self {
+        Self {
+            bytes: Bytes::from_static(value.as_bytes()),
+        }
+    }
+
+    /// Returns the value as bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the value as text, failing if it is not valid UTF-8.
+    pub fn to_str(&self) -> Result<&str, core::str::Utf8Error> {
+        core::str::from_utf8(&self.bytes)
+    }
+}
+
+impl fmt::Debug for HeaderValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to_str() {
+            Ok(value) => fmt::Debug::fmt(value, f),
+            Err(_) => fmt::Debug::fmt(&self.bytes, f),
+        }
+    }
+}
+
+impl From<Bytes> for HeaderValue {
+    fn from(bytes: Bytes) -> Self {
+        Self { bytes }
+    }
+}
+
+impl From<Vec<u8>> for HeaderValue {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::from(Bytes::from(bytes))
+    }
+}
+
+impl From<&[u8]> for HeaderValue {
+    fn from(bytes: &[u8]) -> Self {
+        Self::from(Bytes::copy_from_slice(bytes))
+    }
+}
+
+impl From<String> for HeaderValue {
+    fn from(value: String) -> Self {
+        Self::from(Bytes::from(value))
+    }
+}
+
+impl From<&str> for HeaderValue {
+    fn from(value: &str) -> Self {
+        Self::from(value.as_bytes())
+    }
+}
+
+impl From<&String> for HeaderValue {
+    fn from(value: &String) -> Self {
+        Self::from(value.as_str())
+    }
+}
+
+impl PartialEq<str> for HeaderValue {
+    fn eq(&self, other: &str) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl PartialEq<&str> for HeaderValue {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_case_insensitive() {
+        let mut headers = HeaderMap::new();
+        headers.insert("Content-Type", "text/plain");
+        assert_eq!(headers.get("content-type").unwrap(), "text/plain");
+        assert_eq!(headers.get("CONTENT-TYPE").unwrap(), "text/plain");
+        assert!(headers.contains("Content-type"));
+        assert_eq!(headers.iter().next().unwrap().0, "content-type");
+    }
+
+    #[test]
+    fn insert_replaces_and_append_keeps_values() {
+        let mut headers = HeaderMap::new();
+        headers.append("Accept", "text/html");
+        headers.append("accept", "application/json");
+        assert_eq!(headers.get_all("accept"), ["text/html", "application/json"]);
+        assert_eq!(headers.get("accept").unwrap(), "text/html");
+        assert_eq!(headers.len(), 2);
+
+        headers.insert("ACCEPT", "*/*");
+        assert_eq!(headers.get_all("accept"), ["*/*"]);
+        assert_eq!(headers.len(), 1);
+    }
+
+    #[test]
+    fn remove_and_retain_keep_lookups_consistent() {
+        let mut headers: HeaderMap = [("a", "1"), ("b", "2"), ("c", "3"), ("b", "4")]
+            .into_iter()
+            .collect();
+        assert_eq!(headers.remove("B"), ["2", "4"]);
+        assert!(headers.remove("b").is_empty());
+        assert_eq!(headers.get("c").unwrap(), "3");
+
+        headers.retain(|name| name != "a");
+        assert!(!headers.contains("a"));
+        assert_eq!(headers.get("c").unwrap(), "3");
+        assert_eq!(headers.len(), 1);
+        assert!(!headers.is_empty());
+    }
+
+    #[test]
+    fn equality_ignores_header_order() {
+        let left: HeaderMap = [("a", "1"), ("b", "2")].into_iter().collect();
+        let right: HeaderMap = [("B", "2"), ("A", "1")].into_iter().collect();
+        assert_eq!(left, right);
+
+        let reordered: HeaderMap = [("a", "1"), ("a", "2")].into_iter().collect();
+        let values: HeaderMap = [("a", "2"), ("a", "1")].into_iter().collect();
+        assert_ne!(reordered, values);
+    }
+
+    #[test]
+    fn typed_accessors() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(headers.content_length(), None);
+        headers.insert("Content-Length", " 42 ");
+        assert_eq!(headers.content_length(), Some(42));
+        headers.insert("Content-Length", "nope");
+        assert_eq!(headers.content_length(), None);
+
+        headers.insert("Content-Type", &b"caf\xe9"[..]);
+        assert_eq!(headers.content_type(), None);
+        assert_eq!(headers.get("content-type").unwrap().as_bytes(), b"caf\xe9");
+    }
+}
@@ -10,15 +10,58 @@
 //! # Features
 //!
 //! - `json` - Enables automatic JSON serialization/deserialization support via serde.
+//! - `form` - Enables URL-encoded form serialization/deserialization support via serde.
+//! - `query` - Enables serializing query parameters from serde types.
+//! - `gzip`, `zstd` - Enable compressing request bodies with the corresponding algorithm.
+//! - `cookies` - Enables the [`CookieJar`] cookie store.
 
+mod auth;
+mod body;
+mod cancel;
 mod client;
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+mod compression;
+#[cfg(feature = "cookies")]
+mod cookies;
+mod defaults;
 mod error;
+mod headers;
+mod metadata;
 mod method;
+mod middleware;
+mod multipart;
+#[cfg(feature = "query")]
+mod query;
+mod redirect;
 mod request;
 mod response;
+mod retry;
+mod status;
+mod timeout;
 
+pub use auth::{Token, TokenAuth, TokenProvider};
+pub use body::{BodyContent, BodyStream, BoxError, RequestBody, StreamConsumed};
+pub use cancel::CancellationToken;
 pub use client::HttpClient;
-pub use error::HttpError;
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub use compression::Compression;
+#[cfg(feature = "cookies")]
+pub use cookies::{CookieJar, CookieJarError, PublicSuffixList};
+pub use defaults::Defaults;
+pub use error::{ClassifyError, HttpError, StatusError};
+pub use headers::{HeaderMap, HeaderValue};
+pub use metadata::{HttpVersion, ResponseMetadata, Timings};
 pub use method::HttpMethod;
+pub use middleware::{Layer, MapRequest, Middleware, MiddlewareClient, Next};
+pub use multipart::{Form, Part};
+#[cfg(feature = "query")]
+pub use query::{ArrayFormat, QueryError};
+pub use redirect::{RedirectAction, RedirectAttempt, RedirectPolicy};
 pub use request::{HttpRequest, HttpRequestBuilder};
 pub use response::{HttpResponse, ResponseBody};
+pub use retry::{RetryClient, RetryLayer, RetryPolicy, Timer};
+pub use status::StatusCode;
+pub use timeout::{TimeoutKind, Timeouts};
+
+// Re-exported since it is part of the public API (e.g. `ResponseBody::next_chunk`).
+pub use bytes::Bytes;
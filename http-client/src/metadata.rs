@@ -0,0 +1,65 @@
+use core::fmt;
+use core::time::Duration;
+use std::net::SocketAddr;
+
+/// Information about how a response was received, mostly useful to debug latency and connection
+/// issues.
+///
+/// Every field is optional, as clients may not be able to provide all of them (e.g. mock clients
+/// do not open any connection).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResponseMetadata {
+    /// The HTTP version of the response.
+    pub version: Option<HttpVersion>,
+    /// The address of the server (or proxy) the response was received from.
+    pub remote_addr: Option<SocketAddr>,
+    /// Whether the response was received on a pooled connection that was already used by a
+    /// previous request.
+    pub reused: Option<bool>,
+    /// How long each phase of the request took.
+    pub timings: Timings,
+}
+
+/// The duration of each phase of a request.
+///
+/// The connection phases (`dns`, `connect` and `tls`) are only known when a new connection was
+/// opened for the request, and are `None` when a pooled connection was reused.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timings {
+    /// The time spent resolving the host name.
+    pub dns: Option<Duration>,
+    /// The time spent establishing the TCP connection, after the name was resolved.
+    pub connect: Option<Duration>,
+    /// The time spent on the TLS handshake, after the TCP connection was established.
+    pub tls: Option<Duration>,
+    /// The time from the start of the request, including the connection phases, until the
+    /// response headers were received.
+    pub ttfb: Option<Duration>,
+}
+
+/// An HTTP protocol version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HttpVersion {
+    /// HTTP/0.9
+    Http09,
+    /// HTTP/1.0
+    Http10,
+    /// HTTP/1.1
+    Http11,
+    /// HTTP/2
+    Http2,
+    /// HTTP/3
+    Http3,
+}
+
+impl fmt::Display for HttpVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            HttpVersion::Http09 => "HTTP/0.9",
+            HttpVersion::Http10 => "HTTP/1.0",
+            HttpVersion::Http11 => "HTTP/1.1",
+            HttpVersion::Http2 => "HTTP/2",
+            HttpVersion::Http3 => "HTTP/3",
+        })
+    }
+}
@@ -1,11 +1,21 @@
+use core::fmt;
+
 /// HTTP request methods supported by the client.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum HttpMethod {
     Get,
     Post,
     Put,
     Patch,
     Delete,
+    Head,
+    Options,
+    Trace,
+    Connect,
+    /// Any other method (e.g. WebDAV's `PROPFIND`), given as its token.
+    ///
+    /// Method tokens are case-sensitive and are sent as is.
+    Extension(String),
 }
 
 impl AsRef<str> for HttpMethod {
@@ -16,6 +26,54 @@ impl AsRef<str> for HttpMethod {
             HttpMethod::Put => "PUT",
             HttpMethod::Patch => "PATCH",
             HttpMethod::Delete => "DELETE",
+            HttpMethod::Head => "HEAD",
+            HttpMethod::Options => "OPTIONS",
+            HttpMethod::Trace => "TRACE",
+            HttpMethod::Connect => "CONNECT",
+            HttpMethod::Extension(token) => token,
         }
     }
 }
+
+impl HttpMethod {
+    /// Returns true if sending the request several times has the same effect as sending it once.
+    ///
+    /// This is used to decide whether a request can be safely retried. Extension methods are
+    /// never considered idempotent, since their semantics are unknown.
+    pub fn is_idempotent(&self) -> bool {
+        match self {
+            HttpMethod::Get
+            | HttpMethod::Put
+            | HttpMethod::Delete
+            | HttpMethod::Head
+            | HttpMethod::Options
+            | HttpMethod::Trace => true,
+            HttpMethod::Post | HttpMethod::Patch | HttpMethod::Connect => false,
+            HttpMethod::Extension(_) => false,
+        }
+    }
+}
+
+impl From<&str> for HttpMethod {
+    /// Parses a method token, falling back to [`HttpMethod::Extension`] for non-standard methods.
+    fn from(token: &str) -> Self {
+        match token {
+            "GET" => HttpMethod::Get,
+            "POST" => HttpMethod::Post,
+            "PUT" => HttpMethod::Put,
+            "PATCH" => HttpMethod::Patch,
+            "DELETE" => HttpMethod::Delete,
+            "HEAD" => HttpMethod::Head,
+            "OPTIONS" => HttpMethod::Options,
+            "TRACE" => HttpMethod::Trace,
+            "CONNECT" => HttpMethod::Connect,
+            _ => HttpMethod::Extension(token.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for HttpMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
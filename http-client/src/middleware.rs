@@ -0,0 +1,134 @@
+use core::future::Future;
+
+use crate::client::HttpClient;
+use crate::error::HttpError;
+use crate::request::HttpRequest;
+use crate::response::HttpResponse;
+
+/// Trait for middlewares intercepting the requests sent through a [`MiddlewareClient`].
+///
+/// A middleware receives each request along with the rest of the stack ([`Next`]), and is free to
+/// modify the request, forward it (possibly several times), inspect or modify the response, or
+/// short-circuit the stack by returning a response or an error on its own.
+pub trait Middleware<C: HttpClient>: Send + Sync {
+    /// Handles a request, usually by forwarding it to `next`.
+    fn handle(
+        &self,
+        request: HttpRequest,
+        next: Next<'_, C>,
+    ) -> impl Future<Output = Result<HttpResponse<C::Body>, HttpError<C::Error>>> + Send;
+}
+
+/// The rest of the middleware stack, which a [`Middleware`] uses to send the request.
+pub struct Next<'a, C> {
+    client: &'a C,
+}
+
+impl<'a, C: HttpClient> Next<'a, C> {
+    /// Sends the request through the rest of the stack.
+    pub fn run(
+        self,
+        request: HttpRequest,
+    ) -> impl Future<Output = Result<HttpResponse<C::Body>, HttpError<C::Error>>> + Send + 'a {
+        self.client.send(request)
+    }
+}
+
+impl<C> Clone for Next<'_, C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C> Copy for Next<'_, C> {}
+
+/// An [`HttpClient`] wrapper sending every request through a [`Middleware`].
+///
+/// Middlewares are stacked by wrapping a `MiddlewareClient` in another one, the outermost
+/// middleware being the first to handle requests (see [`HttpClient::with_middleware`]).
+pub struct MiddlewareClient<C, M> {
+    inner: C,
+    middleware: M,
+}
+
+impl<C, M> MiddlewareClient<C, M> {
+    /// Wraps the given client with a middleware.
+    pub fn new(inner: C, middleware: M) -> Self {
+        Self { inner, middleware }
+    }
+
+    /// Returns a reference to the wrapped client.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns a reference to the middleware.
+    pub fn middleware(&self) -> &M {
+        &self.middleware
+    }
+}
+
+impl<C, M> HttpClient for MiddlewareClient<C, M>
+where
+    C: HttpClient + Sync,
+    M: Middleware<C>,
+{
+    type Body = C::Body;
+    type Error = C::Error;
+
+    fn send(
+        &self,
+        request: HttpRequest,
+    ) -> impl Future<Output = Result<HttpResponse<Self::Body>, HttpError<Self::Error>>> + Send {
+        self.middleware.handle(
+            request,
+            Next {
+                client: &self.inner,
+            },
+        )
+    }
+}
+
+/// Trait for types that wrap a client into another one, such as middlewares or retries.
+///
+/// This allows building reusable client stacks that can be applied to any [`HttpClient`] (see
+/// [`HttpClient::with_layer`]).
+pub trait Layer<C> {
+    /// The wrapping client.
+    type Client: HttpClient;
+
+    /// Wraps the given client.
+    fn layer(&self, inner: C) -> Self::Client;
+}
+
+/// A [`Middleware`] modifying every request with a function before sending it.
+///
+/// This is handy for simple cases like adding authentication headers or request IDs.
+#[derive(Debug, Clone)]
+pub struct MapRequest<F> {
+    f: F,
+}
+
+impl<F> MapRequest<F>
+where
+    F: Fn(HttpRequest) -> HttpRequest + Send + Sync,
+{
+    /// Creates a middleware applying `f` to every request.
+    pub fn new(f: F) -> Self {
+        Self { f }
+    }
+}
+
+impl<C, F> Middleware<C> for MapRequest<F>
+where
+    C: HttpClient + Sync,
+    F: Fn(HttpRequest) -> HttpRequest + Send + Sync,
+{
+    fn handle(
+        &self,
+        request: HttpRequest,
+        next: Next<'_, C>,
+    ) -> impl Future<Output = Result<HttpResponse<C::Body>, HttpError<C::Error>>> + Send {
+        next.run((self.f)(request))
+    }
+}
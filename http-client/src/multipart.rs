@@ -0,0 +1,200 @@
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::collections::VecDeque;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use futures_core::Stream;
+
+use crate::body::{BodyContent, BodyStream, BoxError, RequestBody};
+
+/// A `multipart/form-data` form, sent with [`HttpRequestBuilder::multipart`].
+///
+/// If all its parts are buffered, the form is sent as a buffered body. Otherwise, it is streamed,
+/// with a known length if all its streaming parts have one.
+///
+/// [`HttpRequestBuilder::multipart`]: crate::HttpRequestBuilder::multipart
+#[derive(Debug)]
+pub struct Form {
+    boundary: String,
+    parts: Vec<(String, Part)>,
+}
+
+impl Form {
+    /// Creates an empty form with a random boundary.
+    pub fn new() -> Self {
+        let state = RandomState::new();
+        let mut first = state.build_hasher();
+        first.write_u8(0);
+        let mut second = state.build_hasher();
+        second.write_u8(1);
+        Self {
+            boundary: format!("{:016x}{:016x}", first.finish(), second.finish()),
+            parts: Vec::new(),
+        }
+    }
+
+    /// Adds a text field.
+    pub fn text(self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.part(name, Part::text(value))
+    }
+
+    /// Adds a part.
+    pub fn part(mut self, name: impl Into<String>, part: Part) -> Self {
+        self.parts.push((name.into(), part));
+        self
+    }
+
+    /// Returns the boundary separating the parts of the form.
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    /// Returns the `Content-Type` header value of the form, including its boundary.
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    /// Encodes the form into a request body.
+    pub fn into_body(self) -> RequestBody {
+        let mut segments = VecDeque::new();
+        let mut buffer = BytesMut::new();
+        for (name, part) in self.parts {
+            buffer.put_slice(format!("--{}\r\n", self.boundary).as_bytes());
+            buffer.put_slice(b"Content-Disposition: form-data; name=\"");
+            buffer.put_slice(escape(&name).as_bytes());
+            buffer.put_u8(b'"');
+            if let Some(file_name) = &part.file_name {
+                buffer.put_slice(b"; filename=\"");
+                buffer.put_slice(escape(file_name).as_bytes());
+                buffer.put_u8(b'"');
+            }
+            buffer.put_slice(b"\r\n");
+            if let Some(content_type) = &part.content_type {
+                buffer.put_slice(format!("Content-Type: {content_type}\r\n").as_bytes());
+            }
+            buffer.put_slice(b"\r\n");
+
+            match part.body.as_bytes() {
+                Some(bytes) => buffer.put_slice(bytes),
+                None => {
+                    segments.push_back(Segment::Bytes(buffer.split().freeze()));
+                    segments.push_back(Segment::Body(part.body));
+                }
+            }
+            buffer.put_slice(b"\r\n");
+        }
+        buffer.put_slice(format!("--{}--\r\n", self.boundary).as_bytes());
+
+        if segments.is_empty() {
+            return RequestBody::from(buffer.freeze());
+        }
+        segments.push_back(Segment::Bytes(buffer.freeze()));
+        let length = segments
+            .iter()
+            .try_fold(0, |length, segment| match segment {
+                Segment::Bytes(bytes) => Some(length + bytes.len() as u64),
+                Segment::Body(body) => Some(length + body.length()?),
+            });
+        RequestBody::from_stream(
+            MultipartStream {
+                segments,
+                current: None,
+            },
+            length,
+        )
+    }
+}
+
+impl Default for Form {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A part of a multipart [`Form`].
+#[derive(Debug)]
+pub struct Part {
+    body: RequestBody,
+    file_name: Option<String>,
+    content_type: Option<String>,
+}
+
+impl Part {
+    /// Creates a part from a body, which may be buffered or streaming.
+    pub fn new(body: impl Into<RequestBody>) -> Self {
+        Self {
+            body: body.into(),
+            file_name: None,
+            content_type: None,
+        }
+    }
+
+    /// Creates a text part.
+    pub fn text(value: impl Into<String>) -> Self {
+        Self::new(value.into())
+    }
+
+    /// Creates a part from bytes.
+    pub fn bytes(bytes: impl Into<Bytes>) -> Self {
+        Self::new(bytes.into())
+    }
+
+    /// Sets the file name of the part, which makes it a file upload.
+    pub fn file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = Some(file_name.into());
+        self
+    }
+
+    /// Sets the content type of the part (e.g. `image/png`).
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+}
+
+/// Escapes a name or file name to be quoted in a `Content-Disposition` header, like browsers do.
+fn escape(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+enum Segment {
+    Bytes(Bytes),
+    Body(RequestBody),
+}
+
+/// The stream of a form with streaming parts.
+struct MultipartStream {
+    segments: VecDeque<Segment>,
+    current: Option<BodyStream>,
+}
+
+impl Stream for MultipartStream {
+    type Item = Result<Bytes, BoxError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(current) = &mut this.current {
+                match Pin::new(current).poll_next(cx) {
+                    Poll::Ready(None) => this.current = None,
+                    poll => return poll,
+                }
+            }
+
+            match this.segments.pop_front() {
+                None => return Poll::Ready(None),
+                Some(Segment::Bytes(bytes)) => return Poll::Ready(Some(Ok(bytes))),
+                Some(Segment::Body(body)) => match body.into_content() {
+                    Ok(BodyContent::Bytes(bytes)) => return Poll::Ready(Some(Ok(bytes))),
+                    Ok(BodyContent::Stream(stream, _)) => this.current = Some(stream),
+                    Err(e) => return Poll::Ready(Some(Err(Box::new(e)))),
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,538 @@
+use core::fmt;
+
+use serde::ser::{self, Impossible, Serialize};
+
+/// How sequences are expanded into query parameters by
+/// [`HttpRequestBuilder::query_serde_with`](crate::HttpRequestBuilder::query_serde_with).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ArrayFormat {
+    /// Repeats the key for each element: `tags=a&tags=b`.
+    #[default]
+    Repeat,
+    /// Appends `[]` to the key of each element: `tags[]=a&tags[]=b`.
+    Brackets,
+    /// Appends the index to the key of each element: `tags[0]=a&tags[1]=b`.
+    Indices,
+}
+
+/// Error returned when a value cannot be serialized into query parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError(String);
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "query serialization error: {}", self.0)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+impl ser::Error for QueryError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        QueryError(msg.to_string())
+    }
+}
+
+/// Serializes a struct or a map into query parameters.
+///
+/// `None` values are skipped, sequences are expanded according to `format`, and nested structs
+/// and maps use the `parent[child]` notation.
+pub(crate) fn to_pairs<T: Serialize + ?Sized>(
+    value: &T,
+    format: ArrayFormat,
+) -> Result<Vec<(String, String)>, QueryError> {
+    let mut pairs = Vec::new();
+    value.serialize(Serializer {
+        key: None,
+        format,
+        pairs: &mut pairs,
+    })?;
+    Ok(pairs)
+}
+
+/// Serializes a value under the given key, or a struct or map at the top level if there is none.
+struct Serializer<'a> {
+    key: Option<String>,
+    format: ArrayFormat,
+    pairs: &'a mut Vec<(String, String)>,
+}
+
+impl<'a> Serializer<'a> {
+    fn push(self, value: impl ToString) -> Result<(), QueryError> {
+        let key = self
+            .key
+            .ok_or_else(|| QueryError("top-level value must be a struct or a map".into()))?;
+        self.pairs.push((key, value.to_string()));
+        Ok(())
+    }
+
+    fn nested(self) -> Compound<'a> {
+        Compound {
+            key: self.key,
+            format: self.format,
+            pairs: self.pairs,
+            index: 0,
+            pending_key: None,
+        }
+    }
+}
+
+impl<'a> ser::Serializer for Serializer<'a> {
+    type Ok = ();
+    type Error = QueryError;
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Impossible<(), QueryError>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Impossible<(), QueryError>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), QueryError> {
+        self.push(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), QueryError> {
+        self.push(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), QueryError> {
+        self.push(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), QueryError> {
+        self.push(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), QueryError> {
+        self.push(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), QueryError> {
+        self.push(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), QueryError> {
+        self.push(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), QueryError> {
+        self.push(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), QueryError> {
+        self.push(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), QueryError> {
+        self.push(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), QueryError> {
+        self.push(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), QueryError> {
+        self.push(v)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), QueryError> {
+        self.push(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), QueryError> {
+        self.push(v)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), QueryError> {
+        self.push(v)
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<(), QueryError> {
+        Err(QueryError("bytes cannot be serialized into a query".into()))
+    }
+
+    fn serialize_none(self) -> Result<(), QueryError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), QueryError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), QueryError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), QueryError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), QueryError> {
+        self.push(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), QueryError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), QueryError> {
+        let key = child_key(self.key.as_deref(), variant);
+        value.serialize(Serializer {
+            key: Some(key),
+            format: self.format,
+            pairs: self.pairs,
+        })
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a>, QueryError> {
+        if self.key.is_none() {
+            return Err(QueryError(
+                "top-level value must be a struct or a map".into(),
+            ));
+        }
+        Ok(self.nested())
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Compound<'a>, QueryError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a>, QueryError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, QueryError> {
+        Err(QueryError(
+            "tuple variants cannot be serialized into a query".into(),
+        ))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a>, QueryError> {
+        Ok(self.nested())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, QueryError> {
+        Ok(self.nested())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, QueryError> {
+        Err(QueryError(
+            "struct variants cannot be serialized into a query".into(),
+        ))
+    }
+}
+
+/// Returns the key of a field nested in `parent`, if any.
+fn child_key(parent: Option<&str>, field: &str) -> String {
+    match parent {
+        Some(parent) => format!("{parent}[{field}]"),
+        None => field.to_string(),
+    }
+}
+
+/// Serializes the elements of a sequence, or the fields of a struct or map.
+struct Compound<'a> {
+    key: Option<String>,
+    format: ArrayFormat,
+    pairs: &'a mut Vec<(String, String)>,
+    index: usize,
+    pending_key: Option<String>,
+}
+
+impl Compound<'_> {
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), QueryError> {
+        let key = self.key.as_deref().unwrap_or_default();
+        let key = match self.format {
+            ArrayFormat::Repeat => key.to_string(),
+            ArrayFormat::Brackets => format!("{key}[]"),
+            ArrayFormat::Indices => format!("{key}[{}]", self.index),
+        };
+        self.index += 1;
+        value.serialize(Serializer {
+            key: Some(key),
+            format: self.format,
+            pairs: self.pairs,
+        })
+    }
+
+    fn field<T: Serialize + ?Sized>(&mut self, field: &str, value: &T) -> Result<(), QueryError> {
+        value.serialize(Serializer {
+            key: Some(child_key(self.key.as_deref(), field)),
+            format: self.format,
+            pairs: self.pairs,
+        })
+    }
+}
+
+impl ser::SerializeSeq for Compound<'_> {
+    type Ok = ();
+    type Error = QueryError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), QueryError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), QueryError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for Compound<'_> {
+    type Ok = ();
+    type Error = QueryError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), QueryError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), QueryError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for Compound<'_> {
+    type Ok = ();
+    type Error = QueryError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), QueryError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), QueryError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for Compound<'_> {
+    type Ok = ();
+    type Error = QueryError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), QueryError> {
+        // Keys are serialized like any scalar value, then used as field names.
+        let mut pairs = Vec::new();
+        key.serialize(Serializer {
+            key: Some(String::new()),
+            format: self.format,
+            pairs: &mut pairs,
+        })?;
+        match pairs.pop() {
+            Some((_, key)) if pairs.is_empty() => {
+                self.pending_key = Some(key);
+                Ok(())
+            }
+            _ => Err(QueryError("map keys must be scalar values".into())),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), QueryError> {
+        let key = self
+            .pending_key
+            .take()
+            .ok_or_else(|| QueryError("map value serialized before its key".into()))?;
+        self.field(&key, value)
+    }
+
+    fn end(self) -> Result<(), QueryError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for Compound<'_> {
+    type Ok = ();
+    type Error = QueryError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), QueryError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), QueryError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Serialize;
+
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Filter {
+        name: &'static str,
+        page: Option<u32>,
+        limit: Option<u32>,
+        tags: Vec<&'static str>,
+    }
+
+    fn filter() -> Filter {
+        Filter {
+            name: "a b",
+            page: None,
+            limit: Some(10),
+            tags: vec!["x", "y"],
+        }
+    }
+
+    fn pairs(value: &(impl Serialize + ?Sized), format: ArrayFormat) -> Vec<(String, String)> {
+        to_pairs(value, format).unwrap()
+    }
+
+    fn expected(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn array_formats() {
+        assert_eq!(
+            pairs(&filter(), ArrayFormat::Repeat),
+            expected(&[
+                ("name", "a b"),
+                ("limit", "10"),
+                ("tags", "x"),
+                ("tags", "y")
+            ])
+        );
+        assert_eq!(
+            pairs(&filter(), ArrayFormat::Brackets),
+            expected(&[
+                ("name", "a b"),
+                ("limit", "10"),
+                ("tags[]", "x"),
+                ("tags[]", "y")
+            ])
+        );
+        assert_eq!(
+            pairs(&filter(), ArrayFormat::Indices),
+            expected(&[
+                ("name", "a b"),
+                ("limit", "10"),
+                ("tags[0]", "x"),
+                ("tags[1]", "y")
+            ])
+        );
+    }
+
+    #[derive(Serialize)]
+    struct Item {
+        id: u32,
+        active: bool,
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Order {
+        Asc,
+    }
+
+    #[derive(Serialize)]
+    struct Search {
+        filter: Filter,
+        items: Vec<Item>,
+        order: Order,
+        extra: BTreeMap<u32, f64>,
+    }
+
+    #[test]
+    fn nesting() {
+        let search = Search {
+            filter: filter(),
+            items: vec![Item {
+                id: 1,
+                active: true,
+            }],
+            order: Order::Asc,
+            extra: BTreeMap::from([(7, 0.5)]),
+        };
+        assert_eq!(
+            pairs(&search, ArrayFormat::Indices),
+            expected(&[
+                ("filter[name]", "a b"),
+                ("filter[limit]", "10"),
+                ("filter[tags][0]", "x"),
+                ("filter[tags][1]", "y"),
+                ("items[0][id]", "1"),
+                ("items[0][active]", "true"),
+                ("order", "asc"),
+                ("extra[7]", "0.5"),
+            ])
+        );
+        assert_eq!(
+            pairs(&search, ArrayFormat::Brackets)[4],
+            ("items[][id]".to_string(), "1".to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_values() {
+        assert!(to_pairs(&42, ArrayFormat::Repeat).is_err());
+        assert!(to_pairs(&["a", "b"], ArrayFormat::Repeat).is_err());
+
+        let bytes = BTreeMap::from([("data", serde_bytes_like())]);
+        assert!(to_pairs(&bytes, ArrayFormat::Repeat).is_err());
+
+        let complex_keys = BTreeMap::from([((1, 2), "value")]);
+        let err = to_pairs(&complex_keys, ArrayFormat::Repeat).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "query serialization error: map keys must be scalar values"
+        );
+    }
+
+    /// A value serialized as bytes.
+    fn serde_bytes_like() -> impl Serialize {
+        struct Raw;
+
+        impl Serialize for Raw {
+            fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_bytes(b"raw")
+            }
+        }
+
+        Raw
+    }
+}
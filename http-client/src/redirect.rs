@@ -0,0 +1,182 @@
+use core::fmt;
+use std::sync::Arc;
+
+use crate::status::StatusCode;
+
+/// Policy deciding whether a client follows redirect responses.
+///
+/// The default policy follows up to 10 redirects.
+#[derive(Clone)]
+pub struct RedirectPolicy {
+    kind: Kind,
+}
+
+#[derive(Clone)]
+enum Kind {
+    None,
+    Limited(usize),
+    Custom(Arc<dyn Fn(&RedirectAttempt<'_>) -> RedirectAction + Send + Sync>),
+}
+
+impl RedirectPolicy {
+    /// A policy that never follows redirects, returning redirect responses as is.
+    pub fn none() -> Self {
+        Self { kind: Kind::None }
+    }
+
+    /// A policy that follows up to `max` redirects, failing with [`HttpError::Redirect`] if more
+    /// are encountered.
+    ///
+    /// [`HttpError::Redirect`]: crate::HttpError::Redirect
+    pub fn limited(max: usize) -> Self {
+        Self {
+            kind: Kind::Limited(max),
+        }
+    }
+
+    /// A policy calling the given function to decide what to do with each redirect.
+    pub fn custom<F>(policy: F) -> Self
+    where
+        F: Fn(&RedirectAttempt<'_>) -> RedirectAction + Send + Sync + 'static,
+    {
+        Self {
+            kind: Kind::Custom(Arc::new(policy)),
+        }
+    }
+
+    /// Decides what to do with the given redirect.
+    ///
+    /// This is meant to be called by client implementations.
+    pub fn redirect(&self, attempt: &RedirectAttempt<'_>) -> RedirectAction {
+        match &self.kind {
+            Kind::None => RedirectAction::Stop,
+            // The previous URLs include the one answering with this redirect, so they are one
+            // more than the redirects already followed.
+            Kind::Limited(max) if attempt.previous().len() > *max => {
+                RedirectAction::Error(format!("too many redirects (max {max})"))
+            }
+            Kind::Limited(_) => RedirectAction::Follow,
+            Kind::Custom(policy) => policy(attempt),
+        }
+    }
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        Self::limited(10)
+    }
+}
+
+impl fmt::Debug for RedirectPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            Kind::None => f.write_str("RedirectPolicy::None"),
+            Kind::Limited(max) => f.debug_tuple("RedirectPolicy::Limited").field(max).finish(),
+            Kind::Custom(_) => f.write_str("RedirectPolicy::Custom"),
+        }
+    }
+}
+
+/// A redirect about to be followed, as given to a [`RedirectPolicy`].
+#[derive(Debug, Clone, Copy)]
+pub struct RedirectAttempt<'a> {
+    status: StatusCode,
+    url: &'a str,
+    previous: &'a [String],
+}
+
+impl<'a> RedirectAttempt<'a> {
+    /// Creates a new redirect attempt.
+    ///
+    /// `previous` holds the URLs that were already requested, the last one being the URL that
+    /// answered with this redirect.
+    pub fn new(status: impl Into<StatusCode>, url: &'a str, previous: &'a [String]) -> Self {
+        Self {
+            status: status.into(),
+            url,
+            previous,
+        }
+    }
+
+    /// Returns the status code of the redirect response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns the URL the response redirects to.
+    pub fn url(&self) -> &'a str {
+        self.url
+    }
+
+    /// Returns the URLs that were already requested, in order.
+    pub fn previous(&self) -> &'a [String] {
+        self.previous
+    }
+}
+
+/// What to do with a redirect, as decided by a [`RedirectPolicy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedirectAction {
+    /// Follow the redirect.
+    Follow,
+    /// Stop here, returning the redirect response as is.
+    Stop,
+    /// Fail with [`HttpError::Redirect`](crate::HttpError::Redirect) and the given reason.
+    Error(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds the redirect attempt following `followed` redirects.
+    fn decide(policy: &RedirectPolicy, followed: usize) -> RedirectAction {
+        let previous: Vec<String> = (0..=followed)
+            .map(|i| format!("http://example.com/{i}"))
+            .collect();
+        policy.redirect(&RedirectAttempt::new(
+            302,
+            "http://example.com/next",
+            &previous,
+        ))
+    }
+
+    #[test]
+    fn limited_follows_up_to_max_redirects() {
+        let policy = RedirectPolicy::limited(2);
+        assert_eq!(decide(&policy, 0), RedirectAction::Follow);
+        assert_eq!(decide(&policy, 1), RedirectAction::Follow);
+        assert!(matches!(decide(&policy, 2), RedirectAction::Error(_)));
+    }
+
+    #[test]
+    fn limited_zero_follows_nothing() {
+        let policy = RedirectPolicy::limited(0);
+        assert!(matches!(decide(&policy, 0), RedirectAction::Error(_)));
+    }
+
+    #[test]
+    fn default_follows_ten_redirects() {
+        let policy = RedirectPolicy::default();
+        assert_eq!(decide(&policy, 9), RedirectAction::Follow);
+        assert!(matches!(decide(&policy, 10), RedirectAction::Error(_)));
+    }
+
+    #[test]
+    fn none_stops() {
+        assert_eq!(decide(&RedirectPolicy::none(), 0), RedirectAction::Stop);
+    }
+
+    #[test]
+    fn custom_sees_attempt() {
+        let policy = RedirectPolicy::custom(|attempt| {
+            if attempt.status() == StatusCode::FOUND && attempt.previous().len() == 2 {
+                RedirectAction::Stop
+            } else {
+                RedirectAction::Follow
+            }
+        });
+        assert_eq!(decide(&policy, 0), RedirectAction::Follow);
+        assert_eq!(decide(&policy, 1), RedirectAction::Stop);
+    }
+}
@@ -1,4 +1,17 @@
+use core::time::Duration;
+
+use base64::Engine;
+
+use crate::body::RequestBody;
+use crate::cancel::CancellationToken;
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+use crate::compression::Compression;
+use crate::headers::{HeaderMap, HeaderValue};
 use crate::method::HttpMethod;
+use crate::multipart::Form;
+#[cfg(feature = "query")]
+use crate::query::{ArrayFormat, QueryError, to_pairs};
+use crate::timeout::Timeouts;
 
 /// An HTTP request ready to be sent.
 #[derive(Debug, Clone)]
@@ -10,10 +23,23 @@ pub struct HttpRequest {
     /// Query parameters as key-value pairs so that multiple query parameters with the same name
     /// can be added.
     pub query: Vec<(String, String)>,
-    /// HTTP headers as key-value pairs so that multiple headers with the same key can be added.
-    pub headers: Vec<(String, String)>,
+    /// HTTP headers.
+    pub headers: HeaderMap,
     /// Optional request body.
-    pub body: Option<Vec<u8>>,
+    pub body: Option<RequestBody>,
+    /// Timeouts for this request, overriding the defaults of the client.
+    pub timeouts: Timeouts,
+    /// Whether the client may ask for a compressed response and transparently decompress it.
+    ///
+    /// Only has an effect with clients supporting it. Enabled by default.
+    pub decompress: bool,
+    /// Whether the client fails with an [`HttpError::Status`](crate::HttpError::Status) error on
+    /// 4xx and 5xx responses.
+    ///
+    /// Disabled by default.
+    pub error_for_status: bool,
+    /// The token used to cancel the request, whose deadline also bounds the request timeout.
+    pub cancellation: Option<CancellationToken>,
 }
 
 /// Builder for constructing HTTP requests.
@@ -22,8 +48,14 @@ pub struct HttpRequestBuilder {
     method: HttpMethod,
     url: String,
     query: Vec<(String, String)>,
-    headers: Vec<(String, String)>,
-    body: Option<Vec<u8>>,
+    headers: HeaderMap,
+    body: Option<RequestBody>,
+    timeouts: Timeouts,
+    decompress: bool,
+    error_for_status: bool,
+    cancellation: Option<CancellationToken>,
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    compression: Option<Compression>,
 }
 
 impl HttpRequestBuilder {
@@ -33,20 +65,24 @@ impl HttpRequestBuilder {
             method,
             url: url.into(),
             query: Vec::new(),
-            headers: Vec::new(),
+            headers: HeaderMap::new(),
             body: None,
+            timeouts: Timeouts::default(),
+            decompress: true,
+            error_for_status: false,
+            cancellation: None,
+            #[cfg(any(feature = "gzip", feature = "zstd"))]
+            compression: None,
         }
     }
 
     /// Appends a path segment to the URL.
     ///
-    /// The segment will be appended with a `/` separator if needed.
+    /// The segment is always separated from the URL by a single `/`, whether the URL ends with
+    /// one and whether the segment starts with one (e.g. `http://a/b` with `/c` gives
+    /// `http://a/b/c`). An empty segment only adds a trailing `/`.
     pub fn path(mut self, segment: impl AsRef<str>) -> Self {
-        let segment = segment.as_ref();
-        if !self.url.ends_with('/') && !segment.starts_with('/') {
-            self.url.push('/');
-        }
-        self.url.push_str(segment.trim_start_matches('/'));
+        push_path(&mut self.url, segment.as_ref());
         self
     }
 
@@ -56,38 +92,256 @@ impl HttpRequestBuilder {
         self
     }
 
+    /// Adds query parameters by serializing the provided struct or map.
+    ///
+    /// `None` values are skipped, sequences repeat their key for each element (`tags=a&tags=b`),
+    /// and nested structs and maps use the `parent[child]` notation. Use
+    /// [`query_serde_with`](Self::query_serde_with) to format sequences differently.
+    #[cfg(feature = "query")]
+    pub fn query_serde<T: serde::Serialize + ?Sized>(self, value: &T) -> Result<Self, QueryError> {
+        self.query_serde_with(value, ArrayFormat::Repeat)
+    }
+
+    /// Adds query parameters by serializing the provided struct or map, expanding sequences with
+    /// the given format.
+    #[cfg(feature = "query")]
+    pub fn query_serde_with<T: serde::Serialize + ?Sized>(
+        mut self,
+        value: &T,
+        format: ArrayFormat,
+    ) -> Result<Self, QueryError> {
+        self.query.extend(to_pairs(value, format)?);
+        Ok(self)
+    }
+
     /// Adds an HTTP header.
-    pub fn header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
-        self.headers.push((key.into(), value.into()));
+    ///
+    /// Headers that were already added with the same name are kept, so that a header can have
+    /// several values.
+    pub fn header(mut self, key: impl Into<String>, value: impl Into<HeaderValue>) -> Self {
+        self.headers.append(key, value);
+        self
+    }
+
+    /// Sets the `Authorization` header to use HTTP Basic authentication with the given
+    /// credentials.
+    pub fn basic_auth(mut self, username: impl AsRef<str>, password: impl AsRef<str>) -> Self {
+        let credentials = format!("{}:{}", username.as_ref(), password.as_ref());
+        let encoded = base64::engine::general_purpose::STANDARD.encode(credentials);
+        self.headers
+            .insert("Authorization", format!("Basic {encoded}"));
         self
     }
 
-    /// Sets the request body as raw bytes.
-    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+    /// Sets the `Authorization` header to use the given bearer token (e.g. an OAuth 2.0 access
+    /// token).
+    pub fn bearer_auth(mut self, token: impl AsRef<str>) -> Self {
+        self.headers
+            .insert("Authorization", format!("Bearer {}", token.as_ref()));
+        self
+    }
+
+    /// Sets the request body.
+    ///
+    /// This accepts raw bytes (`Vec<u8>`, `String`, `Bytes`, etc.) as well as streaming bodies
+    /// created with [`RequestBody::from_stream`].
+    pub fn body(mut self, body: impl Into<RequestBody>) -> Self {
         self.body = Some(body.into());
         self
     }
 
+    /// Sets the request body as an `application/x-www-form-urlencoded` form, percent-encoding the
+    /// given key-value pairs.
+    ///
+    /// This also sets the `Content-Type` header to `application/x-www-form-urlencoded`.
+    pub fn form<K, V>(mut self, pairs: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let body = form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(pairs)
+            .finish();
+        self.body = Some(body.into());
+        self.headers
+            .insert("Content-Type", "application/x-www-form-urlencoded");
+        self
+    }
+
+    /// Sets the request body as an `application/x-www-form-urlencoded` form, serializing the
+    /// provided value (a struct, a map, a sequence of pairs, etc.).
+    ///
+    /// This also sets the `Content-Type` header to `application/x-www-form-urlencoded`.
+    #[cfg(feature = "form")]
+    pub fn form_serde<T: serde::Serialize + ?Sized>(
+        mut self,
+        value: &T,
+    ) -> Result<Self, serde_urlencoded::ser::Error> {
+        let body = serde_urlencoded::to_string(value)?;
+        self.body = Some(body.into());
+        self.headers
+            .insert("Content-Type", "application/x-www-form-urlencoded");
+        Ok(self)
+    }
+
+    /// Sets the request body as a `multipart/form-data` form.
+    ///
+    /// This also sets the `Content-Type` header, including the boundary of the form.
+    pub fn multipart(mut self, form: Form) -> Self {
+        self.headers.insert("Content-Type", form.content_type());
+        self.body = Some(form.into_body());
+        self
+    }
+
     /// Sets the request body as JSON, serializing the provided value.
     ///
     /// This also sets the `Content-Type` header to `application/json`.
     #[cfg(feature = "json")]
     pub fn json<T: serde::Serialize>(mut self, value: &T) -> Result<Self, serde_json::Error> {
         let json_bytes = serde_json::to_vec(value)?;
-        self.body = Some(json_bytes);
-        self.headers
-            .push(("Content-Type".to_string(), "application/json".to_string()));
+        self.body = Some(json_bytes.into());
+        self.headers.insert("Content-Type", "application/json");
         Ok(self)
     }
 
+    /// Sets the maximum time allowed for the whole request, including reading the response body.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.request = Some(timeout);
+        self
+    }
+
+    /// Sets the maximum time allowed to establish a connection to the server.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.connect = Some(timeout);
+        self
+    }
+
+    /// Sets the maximum time allowed between two reads of the response body.
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.read = Some(timeout);
+        self
+    }
+
+    /// Sets whether the client may ask for a compressed response and transparently decompress it.
+    ///
+    /// Enabled by default. Disable it to receive the response body as sent by the server, along
+    /// with its `Content-Encoding` header.
+    pub fn decompress(mut self, enabled: bool) -> Self {
+        self.decompress = enabled;
+        self
+    }
+
+    /// Sets whether sending the request fails with an [`HttpError::Status`](crate::HttpError::Status)
+    /// error when the server responds with a 4xx or 5xx status.
+    ///
+    /// Disabled by default, in which case such responses are returned like any other.
+    pub fn error_for_status(mut self, enabled: bool) -> Self {
+        self.error_for_status = enabled;
+        self
+    }
+
+    /// Sets the token used to cancel the request.
+    ///
+    /// Sending the request, or reading its response body, fails with
+    /// [`HttpError::Cancelled`](crate::HttpError::Cancelled) once the token is cancelled. If the
+    /// token has a deadline, the request times out when it is reached, as if its request timeout
+    /// elapsed. Pass a [child](CancellationToken::child) of the token of an incoming request to
+    /// make nested calls inherit its cancellation and deadline.
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Compresses the request body with the given algorithm, and sets the `Content-Encoding`
+    /// header accordingly.
+    ///
+    /// The body is compressed when the request is built, so this can be called before or after
+    /// setting it. Streaming bodies, and bodies that already have a `Content-Encoding` header, are
+    /// sent as is.
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    pub fn compress(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
     /// Builds the final HTTP request.
-    pub fn build(self) -> HttpRequest {
+    #[cfg_attr(not(any(feature = "gzip", feature = "zstd")), allow(unused_mut))]
+    pub fn build(mut self) -> HttpRequest {
+        #[cfg(any(feature = "gzip", feature = "zstd"))]
+        if let Some(compression) = self.compression
+            && !self.headers.contains("content-encoding")
+            && let Some(bytes) = self.body.as_ref().and_then(|body| body.as_bytes())
+            && !bytes.is_empty()
+        {
+            self.body = Some(compression.compress(bytes).into());
+            self.headers
+                .insert("Content-Encoding", compression.content_encoding());
+        }
+
         HttpRequest {
             method: self.method,
             url: self.url,
             query: self.query,
             headers: self.headers,
             body: self.body,
+            timeouts: self.timeouts,
+            decompress: self.decompress,
+            error_for_status: self.error_for_status,
+            cancellation: self.cancellation,
         }
     }
 }
+
+/// Appends a path segment to a URL, with a `/` separator if needed.
+pub(crate) fn push_path(url: &mut String, segment: &str) {
+    if !url.ends_with('/') {
+        url.push('/');
+    }
+    url.push_str(segment.trim_start_matches('/'));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn joined(url: &str, segment: &str) -> String {
+        let mut url = url.to_string();
+        push_path(&mut url, segment);
+        url
+    }
+
+    #[test]
+    fn push_path_adds_separator() {
+        assert_eq!(joined("http://a/b", "c"), "http://a/b/c");
+        assert_eq!(joined("http://a", "c/d"), "http://a/c/d");
+    }
+
+    #[test]
+    fn push_path_leading_slashes() {
+        assert_eq!(joined("http://a/b", "/c"), "http://a/b/c");
+        assert_eq!(joined("http://a/b", "//c"), "http://a/b/c");
+        assert_eq!(joined("http://a/b/", "/c"), "http://a/b/c");
+    }
+
+    #[test]
+    fn push_path_trailing_slashes() {
+        assert_eq!(joined("http://a/b/", "c"), "http://a/b/c");
+        assert_eq!(joined("http://a/b", "c/"), "http://a/b/c/");
+    }
+
+    #[test]
+    fn push_path_empty_segments() {
+        assert_eq!(joined("http://a/b", ""), "http://a/b/");
+        assert_eq!(joined("http://a/b/", ""), "http://a/b/");
+        assert_eq!(joined("http://a/b", "/"), "http://a/b/");
+    }
+
+    #[test]
+    fn builder_path() {
+        let request = HttpRequestBuilder::new(HttpMethod::Get, "http://a/b")
+            .path("/c")
+            .path("d")
+            .build();
+        assert_eq!(request.url, "http://a/b/c/d");
+    }
+}
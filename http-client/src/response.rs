@@ -1,30 +1,98 @@
 use core::future::Future;
 
+use bytes::{Bytes, BytesMut};
+
+use crate::error::{HttpError, StatusError};
+use crate::headers::HeaderMap;
+use crate::metadata::ResponseMetadata;
+use crate::status::StatusCode;
+
 /// An HTTP response from the server.
 #[derive(Debug)]
 pub struct HttpResponse<B> {
     /// The HTTP status code.
-    pub status: u16,
-    /// Response headers as key-value pairs.
-    pub headers: Vec<(String, String)>,
+    pub status: StatusCode,
+    /// Response headers.
+    pub headers: HeaderMap,
     /// The response body.
     pub body: B,
+    /// The URL of the response, which differs from the request URL if redirects were followed.
+    pub url: String,
+    /// The URLs that were redirected from before reaching [`url`](Self::url), in order.
+    pub redirects: Vec<String>,
+    /// Information about how the response was received (HTTP version, remote address, timings,
+    /// etc.), as far as the client knows.
+    pub metadata: ResponseMetadata,
 }
 
 impl<B> HttpResponse<B> {
+    /// Returns true if the status code is informational (1xx).
+    pub fn is_informational(&self) -> bool {
+        self.status.is_informational()
+    }
+
     /// Returns true if the status code indicates success (2xx).
     pub fn is_success(&self) -> bool {
-        (200..300).contains(&self.status)
+        self.status.is_success()
+    }
+
+    /// Returns true if the status code is a redirection (3xx).
+    pub fn is_redirection(&self) -> bool {
+        self.status.is_redirection()
     }
 
     /// Returns true if the status code indicates a client error (4xx).
     pub fn is_client_error(&self) -> bool {
-        (400..500).contains(&self.status)
+        self.status.is_client_error()
     }
 
     /// Returns true if the status code indicates a server error (5xx).
     pub fn is_server_error(&self) -> bool {
-        (500..600).contains(&self.status)
+        self.status.is_server_error()
+    }
+
+    /// Returns the response as is if its status is not an error (4xx or 5xx), or an
+    /// [`HttpError::Status`] error otherwise.
+    ///
+    /// The error keeps the headers and the start of the body of the response (see
+    /// [`StatusError::BODY_LIMIT`]), the rest of the body being discarded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_client::{HttpClient, HttpError};
+    ///
+    /// # async fn example<C: HttpClient + Sync>(client: C) -> Result<(), HttpError<C::Error>>
+    /// # where C::Body: Send {
+    /// let request = client.get("https://api.example.com/users/42").build();
+    /// let response = client.send(request).await?.error_for_status().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn error_for_status<E>(self) -> Result<Self, HttpError<E>>
+    where
+        B: ResponseBody,
+    {
+        if !self.is_client_error() && !self.is_server_error() {
+            return Ok(self);
+        }
+
+        let mut body = self.body;
+        let mut prefix = BytesMut::new();
+        while prefix.len() < StatusError::BODY_LIMIT {
+            match body.next_chunk().await {
+                Ok(Some(chunk)) => prefix.extend_from_slice(&chunk),
+                Ok(None) | Err(_) => break,
+            }
+        }
+        prefix.truncate(StatusError::BODY_LIMIT);
+
+        Err(HttpError::Status(Box::new(StatusError::new(
+            self.status,
+            self.headers,
+            prefix.freeze(),
+            self.url,
+        ))))
     }
 }
 
@@ -39,6 +107,12 @@ pub trait ResponseBody: Sized {
     /// Consumes the body and returns it as raw bytes.
     fn into_bytes(self) -> impl Future<Output = Result<Vec<u8>, Self::Error>> + Send;
 
+    /// Reads the next chunk of the body, returning `None` once the body has been fully consumed.
+    ///
+    /// This allows processing large or unbounded bodies (downloads, server-sent events, etc.)
+    /// without buffering them entirely in memory.
+    fn next_chunk(&mut self) -> impl Future<Output = Result<Option<Bytes>, Self::Error>> + Send;
+
     /// Consumes the body and deserializes it as JSON.
     #[cfg(feature = "json")]
     fn json<T: serde::de::DeserializeOwned + Send>(
@@ -53,4 +127,19 @@ pub trait ResponseBody: Sized {
             Ok(serde_json::from_slice(&bytes)?)
         }
     }
+
+    /// Consumes the body and deserializes it as an `application/x-www-form-urlencoded` form.
+    #[cfg(feature = "form")]
+    fn form<T: serde::de::DeserializeOwned + Send>(
+        self,
+    ) -> impl Future<Output = Result<T, Self::Error>> + Send
+    where
+        Self: Send,
+        Self::Error: Send + From<serde_urlencoded::de::Error>,
+    {
+        async move {
+            let bytes = self.into_bytes().await?;
+            Ok(serde_urlencoded::from_bytes(&bytes)?)
+        }
+    }
 }
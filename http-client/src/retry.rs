@@ -0,0 +1,470 @@
+use core::future::Future;
+use core::time::Duration;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Instant, SystemTime};
+
+use crate::client::HttpClient;
+use crate::error::{ClassifyError, HttpError};
+use crate::headers::HeaderMap;
+use crate::middleware::Layer;
+use crate::request::HttpRequest;
+use crate::response::HttpResponse;
+
+/// Trait for async timers, used to wait between retries.
+///
+/// Implementation crates provide an implementation for their runtime (e.g. `TokioTimer` in
+/// `http-client-hyper`).
+pub trait Timer: Send + Sync {
+    /// Waits for the given duration.
+    fn sleep(&self, duration: Duration) -> impl Future<Output = ()> + Send;
+}
+
+/// Configuration of the retries performed by [`RetryClient`].
+///
+/// By default, requests are attempted up to 3 times, with an exponential backoff starting at
+/// 100 milliseconds and capped at 10 seconds, and are retried on connection errors (see
+/// [`HttpError::is_connect`]) as well as on 429, 502, 503 and 504 responses. The delay asked by
+/// the `Retry-After` header of such responses is honored, up to 60 seconds.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    max_retry_after: Duration,
+    jitter: bool,
+    statuses: Vec<u16>,
+    retry_non_idempotent: bool,
+}
+
+impl RetryPolicy {
+    /// Creates a new retry policy with default settings.
+    pub fn new() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            max_retry_after: Duration::from_secs(60),
+            jitter: true,
+            statuses: vec![429, 502, 503, 504],
+            retry_non_idempotent: false,
+        }
+    }
+
+    /// Sets the maximum number of attempts, including the first one.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets the backoff before the first retry, and the maximum backoff between two attempts.
+    ///
+    /// The backoff doubles after each attempt until it reaches `max`.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Sets the maximum delay asked by a `Retry-After` header that is waited for before retrying.
+    ///
+    /// `Retry-After` delays are honored even if they are longer than the maximum backoff. When
+    /// the server asks for a longer delay than `max`, the response is returned as is instead of
+    /// being retried, leaving the caller to decide what to do.
+    pub fn max_retry_after(mut self, max: Duration) -> Self {
+        self.max_retry_after = max;
+        self
+    }
+
+    /// Sets whether a random jitter is applied to the backoff.
+    ///
+    /// With jitter enabled, the actual backoff is randomly picked between half and all of the
+    /// computed backoff, which avoids many clients retrying in lockstep.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets the response status codes that trigger a retry.
+    pub fn retry_statuses(mut self, statuses: impl IntoIterator<Item = u16>) -> Self {
+        self.statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Sets whether requests with a non-idempotent method (e.g. `POST`) are retried too.
+    pub fn retry_non_idempotent(mut self, retry_non_idempotent: bool) -> Self {
+        self.retry_non_idempotent = retry_non_idempotent;
+        self
+    }
+
+    /// Returns true if the given request may be retried.
+    fn can_retry(&self, request: &HttpRequest) -> bool {
+        let replayable = request
+            .body
+            .as_ref()
+            .is_none_or(|body| body.is_replayable());
+        replayable && (self.retry_non_idempotent || request.method.is_idempotent())
+    }
+
+    /// Returns the backoff to wait for after the given (1-based) attempt.
+    fn backoff_for(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff);
+        if !self.jitter {
+            return backoff;
+        }
+
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(attempt);
+        let ratio = (hasher.finish() as f64 / u64::MAX as f64) / 2.0 + 0.5;
+        backoff.mul_f64(ratio)
+    }
+
+    /// Returns the delay before retrying, or `None` if the result should be returned as is.
+    fn retry_delay<B, E: ClassifyError>(
+        &self,
+        attempt: u32,
+        result: &Result<HttpResponse<B>, HttpError<E>>,
+    ) -> Option<Duration> {
+        // Responses turned into errors (see `HttpRequestBuilder::error_for_status`) are retried
+        // like the responses themselves.
+        let response = match result {
+            Ok(response) => Some((response.status, &response.headers)),
+            Err(HttpError::Status(err)) => Some((err.status(), err.headers())),
+            Err(_) => None,
+        };
+
+        if let Some((status, headers)) = response
+            && self.statuses.contains(&status.as_u16())
+        {
+            return match retry_after(headers) {
+                Some(delay) if delay > self.max_retry_after => None,
+                Some(delay) => Some(delay),
+                None => Some(self.backoff_for(attempt)),
+            };
+        }
+
+        match result {
+            // TLS errors (e.g. an invalid certificate) will fail the same way on the next attempt.
+            Err(err) if err.is_connect() && !err.is_tls() => Some(self.backoff_for(attempt)),
+            _ => None,
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns true if the request can still be sent after waiting for `delay`.
+fn within_deadline(request: &HttpRequest, delay: Duration) -> bool {
+    match &request.cancellation {
+        Some(token) if token.is_cancelled() => false,
+        Some(token) => token
+            .deadline()
+            .is_none_or(|deadline| Instant::now() + delay < deadline),
+        None => true,
+    }
+}
+
+/// Parses the `Retry-After` header, which is either a number of seconds or an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get("retry-after")?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// An [`HttpClient`] wrapper that retries failed requests according to a [`RetryPolicy`].
+///
+/// Only requests with an idempotent method are retried, unless
+/// [`RetryPolicy::retry_non_idempotent`] is enabled. Requests with a streaming body are never
+/// retried, since their body cannot be sent twice.
+///
+/// When a response with a retryable status is received on the last attempt, it is returned as is.
+pub struct RetryClient<C, T> {
+    inner: C,
+    timer: T,
+    policy: RetryPolicy,
+}
+
+impl<C, T> RetryClient<C, T> {
+    /// Wraps the given client, using the timer to wait between attempts.
+    pub fn new(inner: C, timer: T) -> Self {
+        Self {
+            inner,
+            timer,
+            policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Sets the retry policy of this client.
+    pub fn with_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns a reference to the wrapped client.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+}
+
+impl<C, T> HttpClient for RetryClient<C, T>
+where
+    C: HttpClient + Sync,
+    C::Error: ClassifyError,
+    T: Timer,
+{
+    type Body = C::Body;
+    type Error = C::Error;
+
+    async fn send(
+        &self,
+        request: HttpRequest,
+    ) -> Result<HttpResponse<Self::Body>, HttpError<Self::Error>> {
+        if !self.policy.can_retry(&request) {
+            return self.inner.send(request).await;
+        }
+
+        let mut attempt = 1;
+        loop {
+            if attempt >= self.policy.max_attempts {
+                return self.inner.send(request).await;
+            }
+
+            let delay = {
+                let result = self.inner.send(request.clone()).await;
+                match self.policy.retry_delay(attempt, &result) {
+                    // Don't retry once the request is cancelled, or if its deadline would be
+                    // reached before the next attempt starts.
+                    Some(delay) if !within_deadline(&request, delay) => return result,
+                    Some(delay) => delay,
+                    None => return result,
+                }
+            };
+
+            self.timer.sleep(delay).await;
+            if request
+                .cancellation
+                .as_ref()
+                .is_some_and(|token| token.is_cancelled())
+            {
+                return Err(HttpError::Cancelled);
+            }
+            attempt += 1;
+        }
+    }
+}
+
+/// A [`Layer`] wrapping clients into a [`RetryClient`].
+#[derive(Debug, Clone)]
+pub struct RetryLayer<T> {
+    timer: T,
+    policy: RetryPolicy,
+}
+
+impl<T> RetryLayer<T> {
+    /// Creates a new layer with the given timer and retry policy.
+    pub fn new(timer: T, policy: RetryPolicy) -> Self {
+        Self { timer, policy }
+    }
+}
+
+impl<C, T> Layer<C> for RetryLayer<T>
+where
+    C: HttpClient + Sync,
+    C::Error: ClassifyError,
+    T: Timer + Clone,
+{
+    type Client = RetryClient<C, T>;
+
+    fn layer(&self, inner: C) -> Self::Client {
+        RetryClient::new(inner, self.timer.clone()).with_policy(self.policy.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use super::*;
+    use crate::method::HttpMethod;
+    use crate::request::HttpRequestBuilder;
+    use crate::timeout::TimeoutKind;
+
+    /// Client error classified according to its fields.
+    #[derive(Debug)]
+    struct TestError {
+        connect: bool,
+        tls: bool,
+    }
+
+    impl ClassifyError for TestError {
+        fn is_connect(&self) -> bool {
+            self.connect
+        }
+
+        fn is_tls(&self) -> bool {
+            self.tls
+        }
+    }
+
+    type TestResult = Result<HttpResponse<()>, HttpError<TestError>>;
+
+    fn response(status: u16, headers: &[(&str, &str)]) -> TestResult {
+        Ok(HttpResponse {
+            status: status.into(),
+            headers: headers.iter().copied().collect(),
+            body: (),
+            url: "http://example.com".to_string(),
+            redirects: Vec::new(),
+            metadata: Default::default(),
+        })
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::new().jitter(false)
+    }
+
+    fn retry_after_header(value: &str) -> Option<Duration> {
+        retry_after(&[("Retry-After", value)].into_iter().collect())
+    }
+
+    #[test]
+    fn retry_after_seconds() {
+        assert_eq!(retry_after_header("120"), Some(Duration::from_secs(120)));
+        assert_eq!(retry_after_header(" 0 "), Some(Duration::ZERO));
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+        assert_eq!(retry_after_header("-1"), None);
+        assert_eq!(retry_after_header("soon"), None);
+    }
+
+    #[test]
+    fn retry_after_http_date() {
+        let date = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(120));
+        let delay = retry_after_header(&date).unwrap();
+        assert!(
+            delay > Duration::from_secs(115) && delay <= Duration::from_secs(120),
+            "{delay:?}"
+        );
+
+        // Dates in the past mean retrying right away
+        let date = httpdate::fmt_http_date(SystemTime::now() - Duration::from_secs(60));
+        assert_eq!(retry_after_header(&date), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn exponential_backoff() {
+        let policy = policy().backoff(Duration::from_millis(100), Duration::from_millis(500));
+        let backoffs: Vec<_> = (1..=5).map(|attempt| policy.backoff_for(attempt)).collect();
+        assert_eq!(
+            backoffs,
+            [100, 200, 400, 500, 500].map(Duration::from_millis)
+        );
+        assert_eq!(policy.backoff_for(u32::MAX), Duration::from_millis(500));
+
+        // The maximum backoff is never lower than the initial one
+        let policy = policy.backoff(Duration::from_secs(2), Duration::from_secs(1));
+        assert_eq!(policy.backoff_for(3), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn jittered_backoff() {
+        let policy = RetryPolicy::new().backoff(Duration::from_secs(1), Duration::from_secs(1));
+        for attempt in 1..20 {
+            let backoff = policy.backoff_for(attempt);
+            assert!(
+                backoff >= Duration::from_millis(500) && backoff <= Duration::from_secs(1),
+                "{backoff:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn retried_statuses() {
+        let policy = policy();
+        assert_eq!(
+            policy.retry_delay(1, &response(503, &[])),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(
+            policy.retry_delay(2, &response(429, &[])),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(policy.retry_delay(1, &response(500, &[])), None);
+        assert_eq!(policy.retry_delay(1, &response(200, &[])), None);
+
+        let policy = policy.retry_statuses([500]);
+        assert!(policy.retry_delay(1, &response(500, &[])).is_some());
+        assert_eq!(policy.retry_delay(1, &response(503, &[])), None);
+    }
+
+    #[test]
+    fn retried_with_retry_after() {
+        let policy = policy();
+        assert_eq!(
+            policy.retry_delay(1, &response(503, &[("Retry-After", "30")])),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            policy.retry_delay(1, &response(503, &[("Retry-After", "61")])),
+            None
+        );
+        // Invalid values fall back to the backoff
+        assert_eq!(
+            policy.retry_delay(1, &response(503, &[("Retry-After", "later")])),
+            Some(Duration::from_millis(100))
+        );
+
+        // Delays longer than the maximum backoff are honored, up to max_retry_after
+        let policy = policy.backoff(Duration::from_millis(100), Duration::from_secs(1));
+        assert_eq!(
+            policy.retry_delay(3, &response(429, &[("Retry-After", "30")])),
+            Some(Duration::from_secs(30))
+        );
+
+        let policy = policy.max_retry_after(Duration::from_secs(5));
+        assert_eq!(
+            policy.retry_delay(1, &response(429, &[("Retry-After", "30")])),
+            None
+        );
+    }
+
+    #[test]
+    fn retried_errors() {
+        let policy = policy();
+        let error =
+            |connect, tls| -> TestResult { Err(HttpError::Client(TestError { connect, tls })) };
+        assert!(policy.retry_delay(1, &error(true, false)).is_some());
+        assert_eq!(policy.retry_delay(1, &error(true, true)), None);
+        assert_eq!(policy.retry_delay(1, &error(false, false)), None);
+
+        let connect_timeout: TestResult = Err(HttpError::Timeout(TimeoutKind::Connect));
+        assert!(policy.retry_delay(1, &connect_timeout).is_some());
+        let request_timeout: TestResult = Err(HttpError::Timeout(TimeoutKind::Request));
+        assert_eq!(policy.retry_delay(1, &request_timeout), None);
+    }
+
+    #[test]
+    fn retried_requests() {
+        let policy = policy();
+        let get = HttpRequestBuilder::new(HttpMethod::Get, "http://example.com").build();
+        let post = HttpRequestBuilder::new(HttpMethod::Post, "http://example.com").build();
+        assert!(policy.can_retry(&get));
+        assert!(!policy.can_retry(&post));
+        assert!(policy.retry_non_idempotent(true).can_retry(&post));
+    }
+}
@@ -0,0 +1,285 @@
+use core::fmt;
+
+/// An HTTP response status code.
+///
+/// Any `u16` can be converted into a status code, including codes without a named constant or
+/// outside of the standard `100..600` range, so that unusual server responses can still be
+/// represented. Status codes can be compared with integers directly:
+///
+/// ```rust
+/// use http_client::StatusCode;
+///
+/// let status = StatusCode::from(404);
+/// assert_eq!(status, StatusCode::NOT_FOUND);
+/// assert_eq!(status, 404);
+/// assert!(status.is_client_error());
+/// assert_eq!(status.to_string(), "404 Not Found");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StatusCode(u16);
+
+impl StatusCode {
+    /// Returns the status code as an integer.
+    pub const fn as_u16(self) -> u16 {
+        self.0
+    }
+
+    /// Returns true if the status code is informational (1xx).
+    pub fn is_informational(self) -> bool {
+        (100..200).contains(&self.0)
+    }
+
+    /// Returns true if the status code indicates success (2xx).
+    pub fn is_success(self) -> bool {
+        (200..300).contains(&self.0)
+    }
+
+    /// Returns true if the status code is a redirection (3xx).
+    pub fn is_redirection(self) -> bool {
+        (300..400).contains(&self.0)
+    }
+
+    /// Returns true if the status code indicates a client error (4xx).
+    pub fn is_client_error(self) -> bool {
+        (400..500).contains(&self.0)
+    }
+
+    /// Returns true if the status code indicates a server error (5xx).
+    pub fn is_server_error(self) -> bool {
+        (500..600).contains(&self.0)
+    }
+}
+
+macro_rules! status_codes {
+    ($($(#[$doc:meta])* ($code:literal, $name:ident, $reason:literal);)+) => {
+        impl StatusCode {
+            $(
+                $(#[$doc])*
+                pub const $name: StatusCode = StatusCode($code);
+            )+
+
+            /// Returns the canonical reason phrase of the status code (e.g. `Not Found` for 404),
+            /// if it is a registered status code.
+            pub fn canonical_reason(self) -> Option<&'static str> {
+                match self.0 {
+                    $($code => Some($reason),)+
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+status_codes! {
+    /// 100 Continue
+    (100, CONTINUE, "Continue");
+    /// 101 Switching Protocols
+    (101, SWITCHING_PROTOCOLS, "Switching Protocols");
+    /// 102 Processing
+    (102, PROCESSING, "Processing");
+    /// 103 Early Hints
+    (103, EARLY_HINTS, "Early Hints");
+
+    /// 200 OK
+    (200, OK, "OK");
+    /// 201 Created
+    (201, CREATED, "Created");
+    /// 202 Accepted
+    (202, ACCEPTED, "Accepted");
+    /// 203 Non-Authoritative Information
+    (203, NON_AUTHORITATIVE_INFORMATION, "Non-Authoritative Information");
+    /// 204 No Content
+    (204, NO_CONTENT, "No Content");
+    /// 205 Reset Content
+    (205, RESET_CONTENT, "Reset Content");
+    /// 206 Partial Content
+    (206, PARTIAL_CONTENT, "Partial Content");
+    /// 207 Multi-Status
+    (207, MULTI_STATUS, "Multi-Status");
+    /// 208 Already Reported
+    (208, ALREADY_REPORTED, "Already Reported");
+    /// 226 IM Used
+    (226, IM_USED, "IM Used");
+
+    /// 300 Multiple Choices
+    (300, MULTIPLE_CHOICES, "Multiple Choices");
+    /// 301 Moved Permanently
+    (301, MOVED_PERMANENTLY, "Moved Permanently");
+    /// 302 Found
+    (302, FOUND, "Found");
+    /// 303 See Other
+    (303, SEE_OTHER, "See Other");
+    /// 304 Not Modified
+    (304, NOT_MODIFIED, "Not Modified");
+    /// 305 Use Proxy
+    (305, USE_PROXY, "Use Proxy");
+    /// 307 Temporary Redirect
+    (307, TEMPORARY_REDIRECT, "Temporary Redirect");
+    /// 308 Permanent Redirect
+    (308, PERMANENT_REDIRECT, "Permanent Redirect");
+
+    /// 400 Bad Request
+    (400, BAD_REQUEST, "Bad Request");
+    /// 401 Unauthorized
+    (401, UNAUTHORIZED, "Unauthorized");
+    /// 402 Payment Required
+    (402, PAYMENT_REQUIRED, "Payment Required");
+    /// 403 Forbidden
+    (403, FORBIDDEN, "Forbidden");
+    /// 404 Not Found
+    (404, NOT_FOUND, "Not Found");
+    /// 405 Method Not Allowed
+    (405, METHOD_NOT_ALLOWED, "Method Not Allowed");
+    /// 406 Not Acceptable
+    (406, NOT_ACCEPTABLE, "Not Acceptable");
+    /// 407 Proxy Authentication Required
+    (407, PROXY_AUTHENTICATION_REQUIRED, "Proxy Authentication Required");
+    /// 408 Request Timeout
+    (408, REQUEST_TIMEOUT, "Request Timeout");
+    /// 409 Conflict
+    (409, CONFLICT, "Conflict");
+    /// 410 Gone
+    (410, GONE, "Gone");
+    /// 411 Length Required
+    (411, LENGTH_REQUIRED, "Length Required");
+    /// 412 Precondition Failed
+    (412, PRECONDITION_FAILED, "Precondition Failed");
+    /// 413 Content Too Large
+    (413, PAYLOAD_TOO_LARGE, "Content Too Large");
+    /// 414 URI Too Long
+    (414, URI_TOO_LONG, "URI Too Long");
+    /// 415 Unsupported Media Type
+    (415, UNSUPPORTED_MEDIA_TYPE, "Unsupported Media Type");
+    /// 416 Range Not Satisfiable
+    (416, RANGE_NOT_SATISFIABLE, "Range Not Satisfiable");
+    /// 417 Expectation Failed
+    (417, EXPECTATION_FAILED, "Expectation Failed");
+    /// 418 I'm a teapot
+    (418, IM_A_TEAPOT, "I'm a teapot");
+    /// 421 Misdirected Request
+    (421, MISDIRECTED_REQUEST, "Misdirected Request");
+    /// 422 Unprocessable Content
+    (422, UNPROCESSABLE_ENTITY, "Unprocessable Content");
+    /// 423 Locked
+    (423, LOCKED, "Locked");
+    /// 424 Failed Dependency
+    (424, FAILED_DEPENDENCY, "Failed Dependency");
+    /// 425 Too Early
+    (425, TOO_EARLY, "Too Early");
+    /// 426 Upgrade Required
+    (426, UPGRADE_REQUIRED, "Upgrade Required");
+    /// 428 Precondition Required
+    (428, PRECONDITION_REQUIRED, "Precondition Required");
+    /// 429 Too Many Requests
+    (429, TOO_MANY_REQUESTS, "Too Many Requests");
+    /// 431 Request Header Fields Too Large
+    (431, REQUEST_HEADER_FIELDS_TOO_LARGE, "Request Header Fields Too Large");
+    /// 451 Unavailable For Legal Reasons
+    (451, UNAVAILABLE_FOR_LEGAL_REASONS, "Unavailable For Legal Reasons");
+
+    /// 500 Internal Server Error
+    (500, INTERNAL_SERVER_ERROR, "Internal Server Error");
+    /// 501 Not Implemented
+    (501, NOT_IMPLEMENTED, "Not Implemented");
+    /// 502 Bad Gateway
+    (502, BAD_GATEWAY, "Bad Gateway");
+    /// 503 Service Unavailable
+    (503, SERVICE_UNAVAILABLE, "Service Unavailable");
+    /// 504 Gateway Timeout
+    (504, GATEWAY_TIMEOUT, "Gateway Timeout");
+    /// 505 HTTP Version Not Supported
+    (505, HTTP_VERSION_NOT_SUPPORTED, "HTTP Version Not Supported");
+    /// 506 Variant Also Negotiates
+    (506, VARIANT_ALSO_NEGOTIATES, "Variant Also Negotiates");
+    /// 507 Insufficient Storage
+    (507, INSUFFICIENT_STORAGE, "Insufficient Storage");
+    /// 508 Loop Detected
+    (508, LOOP_DETECTED, "Loop Detected");
+    /// 510 Not Extended
+    (510, NOT_EXTENDED, "Not Extended");
+    /// 511 Network Authentication Required
+    (511, NETWORK_AUTHENTICATION_REQUIRED, "Network Authentication Required");
+}
+
+impl fmt::Display for StatusCode {
+    /// Formats the status code with its reason phrase, if known (e.g. `404 Not Found`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.canonical_reason() {
+            Some(reason) => write!(f, "{} {reason}", self.0),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
+impl From<u16> for StatusCode {
+    fn from(code: u16) -> Self {
+        StatusCode(code)
+    }
+}
+
+impl From<StatusCode> for u16 {
+    fn from(status: StatusCode) -> Self {
+        status.0
+    }
+}
+
+impl PartialEq<u16> for StatusCode {
+    fn eq(&self, other: &u16) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<StatusCode> for u16 {
+    fn eq(&self, other: &StatusCode) -> bool {
+        *self == other.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn categories() {
+        assert!(StatusCode::CONTINUE.is_informational());
+        assert!(StatusCode::OK.is_success());
+        assert!(StatusCode::from(299).is_success());
+        assert!(StatusCode::PERMANENT_REDIRECT.is_redirection());
+        assert!(StatusCode::NOT_FOUND.is_client_error());
+        assert!(StatusCode::NETWORK_AUTHENTICATION_REQUIRED.is_server_error());
+
+        for code in [0, 99, 600, 999] {
+            let status = StatusCode::from(code);
+            assert!(
+                !status.is_informational()
+                    && !status.is_success()
+                    && !status.is_redirection()
+                    && !status.is_client_error()
+                    && !status.is_server_error(),
+                "{code}"
+            );
+        }
+    }
+
+    #[test]
+    fn reason_phrases_and_display() {
+        assert_eq!(
+            StatusCode::IM_A_TEAPOT.canonical_reason(),
+            Some("I'm a teapot")
+        );
+        assert_eq!(StatusCode::from(299).canonical_reason(), None);
+        assert_eq!(StatusCode::NOT_FOUND.to_string(), "404 Not Found");
+        assert_eq!(StatusCode::from(599).to_string(), "599");
+    }
+
+    #[test]
+    fn conversions_and_comparisons() {
+        let status = StatusCode::from(503);
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(status, 503);
+        assert_eq!(503, status);
+        assert_eq!(u16::from(status), 503);
+        assert_eq!(status.as_u16(), 503);
+        assert!(StatusCode::OK < StatusCode::NOT_FOUND);
+    }
+}
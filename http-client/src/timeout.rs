@@ -0,0 +1,57 @@
+use core::fmt;
+use core::time::Duration;
+
+/// Timeouts applied when sending a request.
+///
+/// Timeouts set on a request take precedence over the defaults of the client sending it.
+///
+/// Timeouts elapsing before the response is received fail [`HttpClient::send`] with
+/// [`HttpError::Timeout`]. Those elapsing while reading the response body are reported by the
+/// body error of the client, whose type depends on the implementation: detect them with
+/// [`ClassifyError::is_timeout`] to handle them independently of the client.
+///
+/// [`HttpClient::send`]: crate::HttpClient::send
+/// [`HttpError::Timeout`]: crate::HttpError::Timeout
+/// [`ClassifyError::is_timeout`]: crate::ClassifyError::is_timeout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timeouts {
+    /// Maximum time allowed to establish a connection to the server.
+    pub connect: Option<Duration>,
+    /// Maximum time allowed for the whole request, from sending it to reading the end of the
+    /// response body.
+    pub request: Option<Duration>,
+    /// Maximum time allowed between two reads of the response body.
+    pub read: Option<Duration>,
+}
+
+impl Timeouts {
+    /// Returns these timeouts, falling back to `defaults` for the ones that are not set.
+    pub fn or(self, defaults: Timeouts) -> Timeouts {
+        Timeouts {
+            connect: self.connect.or(defaults.connect),
+            request: self.request.or(defaults.request),
+            read: self.read.or(defaults.read),
+        }
+    }
+}
+
+/// The kind of timeout that elapsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeoutKind {
+    /// The connection to the server could not be established in time.
+    Connect,
+    /// The whole request did not complete in time.
+    Request,
+    /// No data was received from the server in time while reading the response body.
+    Read,
+}
+
+impl fmt::Display for TimeoutKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeoutKind::Connect => write!(f, "connect timeout"),
+            TimeoutKind::Request => write!(f, "request timeout"),
+            TimeoutKind::Read => write!(f, "read timeout"),
+        }
+    }
+}